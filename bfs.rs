@@ -24,7 +24,157 @@ pub fn bfs(graph: &Vec<Vec<usize>>, s: usize) -> Vec<usize> {
 }
 
 #[snippet]
+pub fn bfs_with_parents(graph: &Vec<Vec<usize>>, s: usize) -> (Vec<usize>, Vec<Option<usize>>) {
+    let inf = (1 << 30) as usize;
+
+    let n = graph.len();
+    let mut dist = vec![inf; n];
+    let mut parents = vec![None; n];
+    let mut que = std::collections::VecDeque::new();
+
+    dist[s] = 0;
+    que.push_back(s);
+
+    while let Some(u) = que.pop_front() {
+        for &v in &graph[u] {
+            if dist[v] != inf {
+                continue;
+            }
+            dist[v] = dist[u] + 1;
+            parents[v] = Some(u);
+            que.push_back(v);
+        }
+    }
+    (dist, parents)
+}
+
+#[snippet]
+pub fn multi_source_bfs(graph: &Vec<Vec<usize>>, sources: &[usize]) -> Vec<usize> {
+    let inf = (1 << 30) as usize;
+
+    let n = graph.len();
+    let mut dist = vec![inf; n];
+    let mut que = std::collections::VecDeque::new();
+
+    for &s in sources {
+        if dist[s] == 0 {
+            continue;
+        }
+        dist[s] = 0;
+        que.push_back(s);
+    }
+
+    while let Some(u) = que.pop_front() {
+        for &v in &graph[u] {
+            if dist[v] != inf {
+                continue;
+            }
+            dist[v] = dist[u] + 1;
+            que.push_back(v);
+        }
+    }
+    dist
+}
+
+// parents を始点までたどって経路を復元します。
+// 始点と到達不能な頂点はどちらも親が None なので、target がそれらの場合は None を返します。
+#[snippet]
+pub fn reconstruct_path(parents: &[Option<usize>], target: usize) -> Option<Vec<usize>> {
+    parents[target]?;
+
+    let mut path = vec![target];
+    let mut v = target;
+    while let Some(u) = parents[v] {
+        path.push(u);
+        v = u;
+    }
+    path.reverse();
+    Some(path)
+}
+
+// targets のいずれかが取り出された時点で打ち切ります。
+#[snippet]
+pub fn bfs_reachable(graph: &Vec<Vec<usize>>, s: usize, targets: &[usize]) -> bool {
+    let n = graph.len();
+    let mut is_target = vec![false; n];
+    for &t in targets {
+        is_target[t] = true;
+    }
+
+    let mut visited = vec![false; n];
+    let mut que = std::collections::VecDeque::new();
+
+    visited[s] = true;
+    que.push_back(s);
+
+    while let Some(u) = que.pop_front() {
+        if is_target[u] {
+            return true;
+        }
+        for &v in &graph[u] {
+            if visited[v] {
+                continue;
+            }
+            visited[v] = true;
+            que.push_back(v);
+        }
+    }
+    false
+}
+
+#[snippet]
+pub fn bfs_visited(graph: &Vec<Vec<usize>>, s: usize) -> Vec<bool> {
+    let n = graph.len();
+    let mut visited = vec![false; n];
+    let mut que = std::collections::VecDeque::new();
+
+    visited[s] = true;
+    que.push_back(s);
+
+    while let Some(u) = que.pop_front() {
+        for &v in &graph[u] {
+            if visited[v] {
+                continue;
+            }
+            visited[v] = true;
+            que.push_back(v);
+        }
+    }
+    visited
+}
+
+// 木の直径
+// 任意の頂点から最も遠い頂点 a を求め、a から最も遠い頂点 b までの距離が直径になります。
+// graph は木（連結かつ閉路なし）である必要があります。
+#[snippet(include = "bfs")]
+pub fn tree_diameter(graph: &Vec<Vec<usize>>) -> (usize, usize, usize) {
+    let farthest = |dist: &Vec<usize>| {
+        let mut v = 0;
+        for (u, &d) in dist.iter().enumerate() {
+            if d > dist[v] {
+                v = u;
+            }
+        }
+        v
+    };
+
+    let a = farthest(&bfs(graph, 0));
+    let dist = bfs(graph, a);
+    let b = farthest(&dist);
+    (dist[b], a, b)
+}
+
+#[snippet(include = "grid_bfs_with")]
 pub fn grid_bfs(field: &Vec<Vec<char>>, s: (usize, usize)) -> Vec<Vec<usize>> {
+    grid_bfs_with(field, s, |c| c != '#')
+}
+
+#[snippet]
+pub fn grid_bfs_with<F: Fn(char) -> bool>(
+    field: &Vec<Vec<char>>,
+    s: (usize, usize),
+    passable: F,
+) -> Vec<Vec<usize>> {
     let inf: usize = 1 << 30;
     let dx: [i32; 4] = [1, 0, -1, 0];
     let dy: [i32; 4] = [0, 1, 0, -1];
@@ -38,9 +188,241 @@ pub fn grid_bfs(field: &Vec<Vec<char>>, s: (usize, usize)) -> Vec<Vec<usize>> {
     let mut dist = vec![vec![inf; w]; h];
     let mut que = std::collections::VecDeque::new();
 
+    if !passable(field[s.0][s.1]) {
+        return dist;
+    }
+
     dist[s.0][s.1] = 0;
     que.push_back(s);
 
+    while let Some((x, y)) = que.pop_front() {
+        for dir in 0..4 {
+            let nx = x as i32 + dx[dir];
+            let ny = y as i32 + dy[dir];
+
+            if nx < 0 || h as i32 <= nx || ny < 0 || w as i32 <= ny {
+                continue;
+            }
+
+            let nx = nx as usize;
+            let ny = ny as usize;
+
+            if !passable(field[nx][ny]) {
+                continue;
+            }
+            if dist[nx][ny] != inf {
+                continue;
+            }
+
+            dist[nx][ny] = dist[x][y] + 1;
+            que.push_back((nx, ny))
+        }
+    }
+    dist
+}
+
+// 各マスに入ってきた方向を記録しておき、t から逆にたどって経路を復元します。
+// grid_bfs と同じく、始点が '#' のときはどこにも到達できないものとして None を返します。
+#[snippet]
+pub fn grid_bfs_path(
+    field: &Vec<Vec<char>>,
+    s: (usize, usize),
+    t: (usize, usize),
+) -> Option<Vec<(usize, usize)>> {
+    let dx: [i32; 4] = [1, 0, -1, 0];
+    let dy: [i32; 4] = [0, 1, 0, -1];
+    let no_dir = 4;
+
+    if field.is_empty() {
+        return None;
+    }
+
+    let h = field.len();
+    let w = field[0].len();
+    let mut seen = vec![vec![false; w]; h];
+    let mut prev_dir = vec![vec![no_dir; w]; h];
+    let mut que = std::collections::VecDeque::new();
+
+    if field[s.0][s.1] == '#' {
+        return None;
+    }
+
+    seen[s.0][s.1] = true;
+    que.push_back(s);
+
+    while let Some((x, y)) = que.pop_front() {
+        if (x, y) == t {
+            break;
+        }
+        for dir in 0..4 {
+            let nx = x as i32 + dx[dir];
+            let ny = y as i32 + dy[dir];
+
+            if nx < 0 || h as i32 <= nx || ny < 0 || w as i32 <= ny {
+                continue;
+            }
+
+            let nx = nx as usize;
+            let ny = ny as usize;
+
+            if field[nx][ny] == '#' {
+                continue;
+            }
+            if seen[nx][ny] {
+                continue;
+            }
+
+            seen[nx][ny] = true;
+            prev_dir[nx][ny] = dir;
+            que.push_back((nx, ny))
+        }
+    }
+
+    if !seen[t.0][t.1] {
+        return None;
+    }
+
+    let mut path = vec![t];
+    let (mut x, mut y) = t;
+    while prev_dir[x][y] != no_dir {
+        let dir = prev_dir[x][y];
+        x = (x as i32 - dx[dir]) as usize;
+        y = (y as i32 - dy[dir]) as usize;
+        path.push((x, y));
+    }
+    path.reverse();
+    Some(path)
+}
+
+#[snippet]
+pub fn grid_bfs_8dir(field: &Vec<Vec<char>>, s: (usize, usize)) -> Vec<Vec<usize>> {
+    let inf: usize = 1 << 30;
+    let dx: [i32; 8] = [1, 0, -1, 0, 1, 1, -1, -1];
+    let dy: [i32; 8] = [0, 1, 0, -1, 1, -1, 1, -1];
+
+    if field.is_empty() {
+        return Vec::new();
+    }
+
+    let h = field.len();
+    let w = field[0].len();
+    let mut dist = vec![vec![inf; w]; h];
+    let mut que = std::collections::VecDeque::new();
+
+    dist[s.0][s.1] = 0;
+    que.push_back(s);
+
+    while let Some((x, y)) = que.pop_front() {
+        for dir in 0..8 {
+            let nx = x as i32 + dx[dir];
+            let ny = y as i32 + dy[dir];
+
+            if nx < 0 || h as i32 <= nx || ny < 0 || w as i32 <= ny {
+                continue;
+            }
+
+            let nx = nx as usize;
+            let ny = ny as usize;
+
+            if field[nx][ny] == '#' {
+                continue;
+            }
+            if dist[nx][ny] != inf {
+                continue;
+            }
+
+            dist[nx][ny] = dist[x][y] + 1;
+            que.push_back((nx, ny))
+        }
+    }
+    dist
+}
+
+#[snippet]
+pub fn label_components(
+    field: &Vec<Vec<char>>,
+    passable: impl Fn(char) -> bool,
+    diagonal: bool,
+) -> (Vec<Vec<i32>>, usize) {
+    let dx: [i32; 8] = [1, 0, -1, 0, 1, 1, -1, -1];
+    let dy: [i32; 8] = [0, 1, 0, -1, 1, -1, 1, -1];
+    let dirs = if diagonal { 8 } else { 4 };
+
+    if field.is_empty() {
+        return (Vec::new(), 0);
+    }
+
+    let h = field.len();
+    let w = field[0].len();
+    let mut label = vec![vec![-1; w]; h];
+    let mut count = 0;
+    let mut que = std::collections::VecDeque::new();
+
+    for i in 0..h {
+        for j in 0..w {
+            if !passable(field[i][j]) || label[i][j] != -1 {
+                continue;
+            }
+
+            label[i][j] = count as i32;
+            que.push_back((i, j));
+
+            while let Some((x, y)) = que.pop_front() {
+                for dir in 0..dirs {
+                    let nx = x as i32 + dx[dir];
+                    let ny = y as i32 + dy[dir];
+
+                    if nx < 0 || h as i32 <= nx || ny < 0 || w as i32 <= ny {
+                        continue;
+                    }
+
+                    let nx = nx as usize;
+                    let ny = ny as usize;
+
+                    if !passable(field[nx][ny]) {
+                        continue;
+                    }
+                    if label[nx][ny] != -1 {
+                        continue;
+                    }
+
+                    label[nx][ny] = count as i32;
+                    que.push_back((nx, ny));
+                }
+            }
+
+            count += 1;
+        }
+    }
+    (label, count)
+}
+
+#[snippet]
+pub fn multi_source_grid_bfs(
+    field: &Vec<Vec<char>>,
+    sources: &[(usize, usize)],
+) -> Vec<Vec<usize>> {
+    let inf: usize = 1 << 30;
+    let dx: [i32; 4] = [1, 0, -1, 0];
+    let dy: [i32; 4] = [0, 1, 0, -1];
+
+    if field.is_empty() {
+        return Vec::new();
+    }
+
+    let h = field.len();
+    let w = field[0].len();
+    let mut dist = vec![vec![inf; w]; h];
+    let mut que = std::collections::VecDeque::new();
+
+    for &s in sources {
+        if dist[s.0][s.1] == 0 {
+            continue;
+        }
+        dist[s.0][s.1] = 0;
+        que.push_back(s);
+    }
+
     while let Some((x, y)) = que.pop_front() {
         for dir in 0..4 {
             let nx = x as i32 + dx[dir];
@@ -152,4 +534,267 @@ mod tests {
         let result = grid_bfs(&field, (0, 0));
         assert!(result.is_empty());
     }
+
+    #[test]
+    fn test_label_components_two_blobs() {
+        let field = vec![
+            vec!['.', '.', '#', '.'],
+            vec!['.', '#', '#', '.'],
+            vec!['#', '#', '.', '.'],
+        ];
+        let (label, count) = label_components(&field, |c| c == '.', false);
+        assert_eq!(count, 2);
+        assert_eq!(
+            label,
+            vec![vec![0, 0, -1, 1], vec![0, -1, -1, 1], vec![-1, -1, 1, 1]]
+        );
+    }
+
+    #[test]
+    fn test_label_components_diagonal_bridge() {
+        let field = vec![
+            vec!['o', '.', '.'],
+            vec!['.', 'o', '.'],
+            vec!['.', '.', 'o'],
+        ];
+
+        let (label, count) = label_components(&field, |c| c == 'o', false);
+        assert_eq!(count, 3);
+        assert_eq!(
+            label,
+            vec![vec![0, -1, -1], vec![-1, 1, -1], vec![-1, -1, 2]]
+        );
+
+        let (label, count) = label_components(&field, |c| c == 'o', true);
+        assert_eq!(count, 1);
+        assert_eq!(
+            label,
+            vec![vec![0, -1, -1], vec![-1, 0, -1], vec![-1, -1, 0]]
+        );
+    }
+
+    #[test]
+    fn test_bfs_with_parents() {
+        let graph = create_graph();
+        let (dist, parents) = bfs_with_parents(&graph, 0);
+        assert_eq!(dist, bfs(&graph, 0));
+        assert_eq!(
+            parents,
+            vec![None, Some(0), Some(0), Some(1), Some(1), Some(2), Some(2)]
+        );
+
+        assert_eq!(reconstruct_path(&parents, 6), Some(vec![0, 2, 6]));
+        assert_eq!(reconstruct_path(&parents, 1), Some(vec![0, 1]));
+        assert_eq!(reconstruct_path(&parents, 0), None);
+
+        let (dist, parents) = bfs_with_parents(&graph, 3);
+        let path = reconstruct_path(&parents, 5).unwrap();
+        assert_eq!(path, vec![3, 1, 0, 2, 5]);
+        assert_eq!(path.len() - 1, dist[5]);
+    }
+
+    #[test]
+    fn test_bfs_with_parents_disconnected_graph() {
+        let graph = vec![vec![1], vec![0], vec![3], vec![2]];
+        let (_, parents) = bfs_with_parents(&graph, 0);
+        assert_eq!(parents, vec![None, Some(0), None, None]);
+        assert_eq!(reconstruct_path(&parents, 1), Some(vec![0, 1]));
+        assert_eq!(reconstruct_path(&parents, 2), None);
+        assert_eq!(reconstruct_path(&parents, 3), None);
+    }
+
+    #[test]
+    fn test_multi_source_bfs() {
+        // 0 - 1 - 2 - 3 - 4 - 5 - 6
+        let n = 7;
+        let mut graph = vec![vec![]; n];
+        for i in 0..n - 1 {
+            graph[i].push(i + 1);
+            graph[i + 1].push(i);
+        }
+        let result = multi_source_bfs(&graph, &[0, 6]);
+        assert_eq!(result, vec![0, 1, 2, 3, 2, 1, 0]);
+
+        let result = multi_source_bfs(&graph, &[1, 5, 1]);
+        assert_eq!(result, vec![1, 0, 1, 2, 1, 0, 1]);
+
+        assert_eq!(multi_source_bfs(&graph, &[2]), bfs(&graph, 2));
+    }
+
+    #[test]
+    fn test_multi_source_grid_bfs() {
+        let field = vec![
+            vec!['.', '.', '.', '.'],
+            vec!['.', '#', '#', '.'],
+            vec!['.', '.', '.', '.'],
+        ];
+        let result = multi_source_grid_bfs(&field, &[(0, 0), (2, 3)]);
+        let inf = (1 << 30) as usize;
+        assert_eq!(
+            result,
+            vec![vec![0, 1, 2, 2], vec![1, inf, inf, 1], vec![2, 2, 1, 0]]
+        );
+
+        assert_eq!(
+            multi_source_grid_bfs(&field, &[(0, 0)]),
+            grid_bfs(&field, (0, 0))
+        );
+    }
+
+    #[test]
+    fn test_grid_bfs_8dir_obstacles() {
+        let field = vec![
+            vec!['.', '.', '.'],
+            vec!['.', '#', '.'],
+            vec!['.', '.', '.'],
+        ];
+        let result = grid_bfs_8dir(&field, (0, 0));
+        let inf = (1 << 30) as usize;
+        assert_eq!(result, vec![vec![0, 1, 2], vec![1, inf, 2], vec![2, 2, 3]]);
+    }
+
+    #[test]
+    fn test_grid_bfs_8dir_diagonal_shortcut() {
+        let field = vec![
+            vec!['.', '#', '.'],
+            vec!['#', '.', '#'],
+            vec!['.', '#', '.'],
+        ];
+        let inf = (1 << 30) as usize;
+
+        let result = grid_bfs(&field, (0, 0));
+        assert_eq!(result[2][2], inf);
+
+        let result = grid_bfs_8dir(&field, (0, 0));
+        assert_eq!(
+            result,
+            vec![vec![0, inf, 2], vec![inf, 1, inf], vec![2, inf, 2]]
+        );
+    }
+
+    #[test]
+    fn test_grid_bfs_with_custom_passable() {
+        let field = vec![
+            vec!['.', 'o', 'X'],
+            vec!['#', 'X', '.'],
+            vec!['.', 'o', '.'],
+        ];
+        let inf = (1 << 30) as usize;
+
+        let result = grid_bfs_with(&field, (0, 0), |c| c != 'X');
+        assert_eq!(
+            result,
+            vec![vec![0, 1, inf], vec![1, inf, 5], vec![2, 3, 4]]
+        );
+
+        let result = grid_bfs_with(&field, (0, 0), |c| c == '.' || c == 'o');
+        assert_eq!(
+            result,
+            vec![vec![0, 1, inf], vec![inf, inf, inf], vec![inf, inf, inf]]
+        );
+    }
+
+    #[test]
+    fn test_grid_bfs_with_impassable_start() {
+        let field = vec![vec!['#', '.'], vec!['.', '.']];
+        let inf = (1 << 30) as usize;
+        let result = grid_bfs_with(&field, (0, 0), |c| c != '#');
+        assert_eq!(result, vec![vec![inf, inf], vec![inf, inf]]);
+    }
+
+    #[test]
+    fn test_grid_bfs_path() {
+        let field = vec![
+            vec!['.', '.', '.'],
+            vec!['.', '#', '.'],
+            vec!['.', '.', '.'],
+        ];
+        let dist = grid_bfs(&field, (0, 0));
+
+        let path = grid_bfs_path(&field, (0, 0), (2, 2)).unwrap();
+        assert_eq!(path.len() - 1, dist[2][2]);
+        assert_eq!(path[0], (0, 0));
+        assert_eq!(path[path.len() - 1], (2, 2));
+        for i in 1..path.len() {
+            let (px, py) = path[i - 1];
+            let (x, y) = path[i];
+            assert_eq!(px.abs_diff(x) + py.abs_diff(y), 1);
+            assert_ne!(field[x][y], '#');
+        }
+
+        let path = grid_bfs_path(&field, (0, 0), (1, 2)).unwrap();
+        assert_eq!(path.len() - 1, dist[1][2]);
+
+        assert_eq!(grid_bfs_path(&field, (1, 0), (1, 0)), Some(vec![(1, 0)]));
+    }
+
+    #[test]
+    fn test_grid_bfs_path_unreachable() {
+        let field = vec![
+            vec!['.', '#', '.'],
+            vec!['#', '#', '#'],
+            vec!['.', '#', '.'],
+        ];
+        assert_eq!(grid_bfs_path(&field, (0, 0), (2, 2)), None);
+    }
+
+    #[test]
+    fn test_grid_bfs_path_impassable_start() {
+        let field = vec![vec!['#', '.'], vec!['.', '.']];
+        assert_eq!(grid_bfs_path(&field, (0, 0), (1, 1)), None);
+        assert_eq!(grid_bfs_path(&field, (0, 0), (0, 0)), None);
+
+        // path and distance agree for every start and target
+        for s in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+            let dist = grid_bfs(&field, s);
+            for t in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+                match grid_bfs_path(&field, s, t) {
+                    Some(path) => assert_eq!(path.len() - 1, dist[t.0][t.1]),
+                    None => assert_eq!(dist[t.0][t.1], 1 << 30),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_tree_diameter_path() {
+        // 2 - 0 - 3 - 1 - 4
+        let graph = vec![vec![2, 3], vec![3, 4], vec![0], vec![0, 1], vec![1]];
+        assert_eq!(tree_diameter(&graph), (4, 4, 2));
+    }
+
+    #[test]
+    fn test_tree_diameter_star() {
+        let graph = vec![vec![1, 2, 3, 4], vec![0], vec![0], vec![0], vec![0]];
+        assert_eq!(tree_diameter(&graph), (2, 1, 2));
+    }
+
+    #[test]
+    fn test_tree_diameter_single_node() {
+        let graph = vec![vec![]];
+        assert_eq!(tree_diameter(&graph), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_bfs_reachable() {
+        let graph = create_graph();
+        assert!(bfs_reachable(&graph, 0, &[6]));
+        assert!(bfs_reachable(&graph, 3, &[5, 6]));
+        assert!(bfs_reachable(&graph, 4, &[4]));
+        assert!(!bfs_reachable(&graph, 0, &[]));
+
+        let graph = vec![vec![1], vec![0], vec![3], vec![2]];
+        assert!(bfs_reachable(&graph, 0, &[1]));
+        assert!(!bfs_reachable(&graph, 0, &[2, 3]));
+        assert!(bfs_reachable(&graph, 0, &[3, 1]));
+    }
+
+    #[test]
+    fn test_bfs_visited() {
+        let graph = create_graph();
+        assert_eq!(bfs_visited(&graph, 5), vec![true; 7]);
+
+        let graph = vec![vec![1], vec![0], vec![3], vec![2]];
+        assert_eq!(bfs_visited(&graph, 2), vec![false, false, true, true]);
+    }
 }
@@ -1,5 +1,7 @@
 use cargo_snippet::snippet;
 
+use crate::modulo::mod_pow;
+
 #[snippet("ModComb")]
 pub struct ModComb {
     modulo: usize,
@@ -44,12 +46,45 @@ impl ModComb {
         self.fac[n] * (self.finv[k] * self.finv[n - k] % self.modulo) % self.modulo
     }
 
+    // 順列
+    // nPk = n!/(n-k)! = (n!) * ((n-k)!)^(-1)
+    pub fn permutation(&self, n: usize, k: usize) -> usize {
+        if n < k {
+            return 0;
+        }
+        self.fac[n] * self.finv[n - k] % self.modulo
+    }
+
     // 重複組合せ
     // n種類のものから重複を許してk個選ぶ場合の数: nHk = (n+k-1)Ck
     pub fn homogeneous(&self, n: usize, k: usize) -> usize {
         self.combination(n + k - 1, k)
     }
 
+    // 多項係数
+    // (Σc_i)! / Π(c_i!) = (Σc_i)! * Π(c_i!)^(-1)
+    // 合計が cap 以上のときは計算できないので 0 を返します。
+    pub fn multinomial(&self, counts: &[usize]) -> usize {
+        let total = counts.iter().sum::<usize>();
+        if total >= self.fac.len() {
+            return 0;
+        }
+        let mut res = self.fac[total];
+        for &c in counts {
+            res = res * self.finv[c] % self.modulo;
+        }
+        res
+    }
+
+    // カタラン数
+    // Cat(n) = 2nCn / (n+1) = (2n)! * (n!)^(-1) * ((n+1)!)^(-1)
+    //
+    // 使用可能場面
+    //   * cap > 2n （cap > n+1 も必要）
+    pub fn catalan(&self, n: usize) -> usize {
+        self.fac[2 * n] * (self.finv[n] * self.finv[n + 1] % self.modulo) % self.modulo
+    }
+
     // 参考：https://algo-logic.info/combination/
     // 計算量：O(k)
     //
@@ -69,6 +104,34 @@ impl ModComb {
     }
 }
 
+// Lucas の定理
+// n, k を p 進数で表したときの各桁を n_i, k_i とすると nCk ≡ Π(n_i C k_i)   (mod.p)
+//
+// 使用可能場面
+//   * pは素数（小さくてもよい）
+//   * 計算量：O(p log_p n)
+#[snippet(include = "mod_pow")]
+pub fn lucas_combination(mut n: usize, mut k: usize, p: usize) -> usize {
+    let mut res = 1;
+    while k > 0 {
+        let (ni, ki) = (n % p, k % p);
+        if ni < ki {
+            return 0;
+        }
+        // ni < p なので分母の各因数は p で割り切れない
+        let mut num = 1;
+        let mut den = 1;
+        for i in 0..ki {
+            num = num * (ni - i) % p;
+            den = den * (i + 1) % p;
+        }
+        res = res * num % p * mod_pow(den, p - 2, p) % p;
+        n /= p;
+        k /= p;
+    }
+    res
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,4 +185,81 @@ mod tests {
         assert_eq!(comb.large_n_combination(large_n, 141421), 516595147);
         assert_eq!(comb.large_n_combination(large_n, 173205), 589953354);
     }
+
+    #[test]
+    fn test_mod_permutation() {
+        let modulo = 1_000_000_007;
+        let cap = 1001;
+        let comb = ModComb::new(cap, modulo);
+
+        // Test permutations
+        assert_eq!(comb.permutation(5, 2), 20);
+        assert_eq!(comb.permutation(10, 0), 1);
+        assert_eq!(comb.permutation(6, 6), 720);
+        assert_eq!(comb.permutation(3, 5), 0);
+
+        // nPk = nCk * k!
+        assert_eq!(
+            comb.permutation(1000, 500),
+            comb.combination(1000, 500) * comb.fac[500] % modulo
+        );
+    }
+
+    #[test]
+    fn test_lucas_combination() {
+        assert_eq!(lucas_combination(10, 3, 3), 0); // 10C3 = 120
+        assert_eq!(lucas_combination(5, 2, 3), 1); // 5C2 = 10
+        assert_eq!(lucas_combination(1000, 3, 13), 12);
+        assert_eq!(lucas_combination(100, 30, 13), 7);
+
+        // Compare against Pascal's triangle
+        let mut row: Vec<u128> = vec![1];
+        for n in 0..=60 {
+            for p in [2, 3, 5, 7, 11] {
+                for (k, &binom) in row.iter().enumerate() {
+                    assert_eq!(lucas_combination(n, k, p), (binom % p as u128) as usize);
+                }
+                assert_eq!(lucas_combination(n, n + 1, p), 0);
+            }
+            let mut next = vec![1; n + 2];
+            for k in 1..=n {
+                next[k] = row[k - 1] + row[k];
+            }
+            row = next;
+        }
+    }
+
+    #[test]
+    fn test_mod_catalan() {
+        let modulo = 1_000_000_007;
+        let cap = 1001;
+        let comb = ModComb::new(cap, modulo);
+
+        let expected = [1, 1, 2, 5, 14, 42, 132, 429];
+        for (n, &c) in expected.iter().enumerate() {
+            assert_eq!(comb.catalan(n), c);
+        }
+
+        // Cat(n) = 2nCn - 2nC(n+1)
+        assert_eq!(
+            comb.catalan(500),
+            (comb.combination(1000, 500) + modulo - comb.combination(1000, 501)) % modulo
+        );
+    }
+
+    #[test]
+    fn test_mod_multinomial() {
+        let modulo = 1_000_000_007;
+        let cap = 1001;
+        let comb = ModComb::new(cap, modulo);
+
+        assert_eq!(comb.multinomial(&[2, 2]), 6);
+        assert_eq!(comb.multinomial(&[1, 1, 1]), 6);
+        assert_eq!(comb.multinomial(&[3, 2, 1]), 60);
+        assert_eq!(comb.multinomial(&[]), 1);
+        assert_eq!(comb.multinomial(&[500, 500]), comb.combination(1000, 500));
+
+        // The total exceeds the table size
+        assert_eq!(comb.multinomial(&[500, 501]), 0);
+    }
 }
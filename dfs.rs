@@ -11,6 +11,217 @@ pub fn dfs(graph: &Vec<Vec<usize>>, seen: &mut Vec<bool>, v: usize) {
     }
 }
 
+// 再帰を使わず、明示的なスタックで行きがけ順を返します。
+// 隣接リストを逆順に積むので、訪問順は再帰版の dfs と同じになります。
+#[snippet]
+pub fn dfs_order(graph: &Vec<Vec<usize>>, start: usize) -> Vec<usize> {
+    let mut seen = vec![false; graph.len()];
+    let mut order = vec![];
+    let mut stack = vec![start];
+
+    while let Some(v) = stack.pop() {
+        if seen[v] {
+            continue;
+        }
+        seen[v] = true;
+        order.push(v);
+        for &next_v in graph[v].iter().rev() {
+            if !seen[next_v] {
+                stack.push(next_v);
+            }
+        }
+    }
+
+    order
+}
+
+// 連結成分の個数と、各頂点が属する成分の番号 (0-indexed) を返します。
+// 成分の番号は、その成分で最も小さい頂点の順に振られます。
+#[snippet]
+pub fn connected_components(graph: &Vec<Vec<usize>>) -> (usize, Vec<usize>) {
+    let n = graph.len();
+    let mut comp_id = vec![usize::MAX; n];
+    let mut count = 0;
+
+    for s in 0..n {
+        if comp_id[s] != usize::MAX {
+            continue;
+        }
+        comp_id[s] = count;
+        let mut stack = vec![s];
+        while let Some(v) = stack.pop() {
+            for &next_v in &graph[v] {
+                if comp_id[next_v] == usize::MAX {
+                    comp_id[next_v] = count;
+                    stack.push(next_v);
+                }
+            }
+        }
+        count += 1;
+    }
+
+    (count, comp_id)
+}
+
+// 無向グラフに閉路があるかを判定します。
+// 親へ戻る辺を1本だけ無視するので、多重辺も閉路として扱います。
+#[snippet]
+pub fn has_cycle_undirected(graph: &Vec<Vec<usize>>) -> bool {
+    let n = graph.len();
+    let mut seen = vec![false; n];
+
+    for s in 0..n {
+        if seen[s] {
+            continue;
+        }
+        seen[s] = true;
+        // (頂点, 親)
+        let mut stack = vec![(s, usize::MAX)];
+        while let Some((v, parent)) = stack.pop() {
+            let mut skipped_parent = false;
+            for &next_v in &graph[v] {
+                if next_v == parent && !skipped_parent {
+                    skipped_parent = true;
+                    continue;
+                }
+                if seen[next_v] {
+                    return true;
+                }
+                seen[next_v] = true;
+                stack.push((next_v, v));
+            }
+        }
+    }
+
+    false
+}
+
+// 有向グラフに閉路があるかを判定します。
+// 0: 未訪問, 1: 探索中（スタック上）, 2: 探索済み として、探索中の頂点へ戻る辺があれば閉路です。
+#[snippet]
+pub fn has_cycle_directed(graph: &Vec<Vec<usize>>) -> bool {
+    let n = graph.len();
+    let mut color = vec![0u8; n];
+
+    for s in 0..n {
+        if color[s] != 0 {
+            continue;
+        }
+        color[s] = 1;
+        // (頂点, 次に見る隣接リストの添字)
+        let mut stack = vec![(s, 0)];
+        while let Some(&(v, i)) = stack.last() {
+            if i == graph[v].len() {
+                color[v] = 2;
+                stack.pop();
+                continue;
+            }
+            stack.last_mut().unwrap().1 += 1;
+            let next_v = graph[v][i];
+            match color[next_v] {
+                0 => {
+                    color[next_v] = 1;
+                    stack.push((next_v, 0));
+                }
+                1 => return true,
+                _ => {}
+            }
+        }
+    }
+
+    false
+}
+
+// Kahn のアルゴリズムでトポロジカル順序を返します。閉路があれば None です。
+// 入次数が 0 になった頂点を FIFO で取り出すので、最初は番号の小さい順に並びます。
+#[snippet]
+pub fn topological_sort(graph: &Vec<Vec<usize>>) -> Option<Vec<usize>> {
+    let n = graph.len();
+    let mut indegree = vec![0; n];
+    for edges in graph {
+        for &v in edges {
+            indegree[v] += 1;
+        }
+    }
+
+    let mut queue: std::collections::VecDeque<usize> =
+        (0..n).filter(|&v| indegree[v] == 0).collect();
+    let mut order = Vec::with_capacity(n);
+    while let Some(v) = queue.pop_front() {
+        order.push(v);
+        for &next_v in &graph[v] {
+            indegree[next_v] -= 1;
+            if indegree[next_v] == 0 {
+                queue.push_back(next_v);
+            }
+        }
+    }
+
+    // 閉路上の頂点は入次数が 0 にならず、取り出されずに残ります。
+    if order.len() == n {
+        Some(order)
+    } else {
+        None
+    }
+}
+
+// 二部グラフ判定。各頂点を 0/1 で塗り分けられればその色を、奇閉路があれば None を返します。
+// 連結成分ごとに、番号の最も小さい頂点を色 0 とします。
+#[snippet]
+pub fn two_coloring(graph: &Vec<Vec<usize>>) -> Option<Vec<u8>> {
+    let n = graph.len();
+    let mut color = vec![u8::MAX; n];
+
+    for s in 0..n {
+        if color[s] != u8::MAX {
+            continue;
+        }
+        color[s] = 0;
+        let mut stack = vec![s];
+        while let Some(v) = stack.pop() {
+            for &next_v in &graph[v] {
+                if color[next_v] == u8::MAX {
+                    color[next_v] = 1 - color[v];
+                    stack.push(next_v);
+                } else if color[next_v] == color[v] {
+                    return None;
+                }
+            }
+        }
+    }
+
+    Some(color)
+}
+
+// root を根とする木で、各頂点の部分木のサイズを返します。
+// 行きがけ順を逆にたどると子が親より先に来るので、再帰なしで子のサイズを親に足せます。
+#[snippet]
+pub fn subtree_sizes(graph: &Vec<Vec<usize>>, root: usize) -> Vec<usize> {
+    let n = graph.len();
+    let mut parent = vec![usize::MAX; n];
+    let mut order = Vec::with_capacity(n);
+    let mut stack = vec![root];
+    parent[root] = root;
+
+    while let Some(v) = stack.pop() {
+        order.push(v);
+        for &next_v in &graph[v] {
+            if parent[next_v] == usize::MAX {
+                parent[next_v] = v;
+                stack.push(next_v);
+            }
+        }
+    }
+
+    let mut size = vec![1; n];
+    for &v in order.iter().rev() {
+        if v != root {
+            size[parent[v]] += size[v];
+        }
+    }
+    size
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,4 +261,227 @@ mod tests {
         dfs(&graph, &mut seen, 0);
         assert_eq!(seen, vec![true, true, false, false]);
     }
+
+    #[test]
+    fn test_dfs_order() {
+        let graph = create_graph();
+        assert_eq!(dfs_order(&graph, 0), vec![0, 1, 3, 4, 2, 5, 6]);
+        assert_eq!(dfs_order(&graph, 5), vec![5, 2, 0, 1, 3, 4, 6]);
+
+        let graph = vec![vec![1], vec![0], vec![3], vec![2]];
+        for start in 0..graph.len() {
+            let mut seen = vec![false; graph.len()];
+            dfs(&graph, &mut seen, start);
+            let mut visited = vec![false; graph.len()];
+            for v in dfs_order(&graph, start) {
+                visited[v] = true;
+            }
+            assert_eq!(visited, seen);
+        }
+    }
+
+    #[test]
+    fn test_dfs_order_matches_recursive_order() {
+        fn rec(graph: &Vec<Vec<usize>>, seen: &mut Vec<bool>, v: usize, order: &mut Vec<usize>) {
+            seen[v] = true;
+            order.push(v);
+            for &next_v in &graph[v] {
+                if !seen[next_v] {
+                    rec(graph, seen, next_v, order);
+                }
+            }
+        }
+
+        // 閉路を含むグラフでも再帰版と同じ順序になることを確認します。
+        let graph = vec![
+            vec![2, 1],
+            vec![0, 3, 2],
+            vec![1, 0, 4],
+            vec![1, 4],
+            vec![3, 2],
+        ];
+        for start in 0..graph.len() {
+            let mut seen = vec![false; graph.len()];
+            let mut order = vec![];
+            rec(&graph, &mut seen, start, &mut order);
+            assert_eq!(dfs_order(&graph, start), order);
+        }
+    }
+
+    #[test]
+    fn test_dfs_order_long_chain() {
+        let n = 200_000;
+        let mut graph = vec![vec![]; n];
+        for i in 0..n - 1 {
+            graph[i].push(i + 1);
+            graph[i + 1].push(i);
+        }
+        let order = dfs_order(&graph, 0);
+        assert_eq!(order, (0..n).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let graph = vec![vec![1], vec![0], vec![3], vec![2]];
+        assert_eq!(connected_components(&graph), (2, vec![0, 0, 1, 1]));
+
+        let graph = create_graph();
+        assert_eq!(connected_components(&graph), (1, vec![0; 7]));
+
+        let graph = vec![vec![], vec![3], vec![], vec![1, 4], vec![3]];
+        assert_eq!(connected_components(&graph), (3, vec![0, 1, 2, 1, 1]));
+
+        assert_eq!(connected_components(&vec![]), (0, vec![]));
+    }
+
+    #[test]
+    fn test_has_cycle_undirected() {
+        // tree
+        assert!(!has_cycle_undirected(&create_graph()));
+        // forest
+        assert!(!has_cycle_undirected(&vec![
+            vec![1],
+            vec![0],
+            vec![3],
+            vec![2]
+        ]));
+        // triangle
+        assert!(has_cycle_undirected(&vec![
+            vec![1, 2],
+            vec![0, 2],
+            vec![0, 1]
+        ]));
+        // cycle only in the second component
+        let graph = vec![vec![1], vec![0], vec![3, 4], vec![2, 4], vec![2, 3]];
+        assert!(has_cycle_undirected(&graph));
+        // multi-edge and self-loop
+        assert!(has_cycle_undirected(&vec![vec![1, 1], vec![0, 0]]));
+        assert!(has_cycle_undirected(&vec![vec![0, 0]]));
+    }
+
+    #[test]
+    fn test_has_cycle_directed() {
+        // DAG: 0 -> 1 -> 3, 0 -> 2 -> 3
+        let graph = vec![vec![1, 2], vec![3], vec![3], vec![]];
+        assert!(!has_cycle_directed(&graph));
+        // back edge 3 -> 0
+        let graph = vec![vec![1, 2], vec![3], vec![3], vec![0]];
+        assert!(has_cycle_directed(&graph));
+        // cross edge 2 -> 1 is not a cycle
+        let graph = vec![vec![1, 2], vec![], vec![1]];
+        assert!(!has_cycle_directed(&graph));
+        // self-loop
+        assert!(has_cycle_directed(&vec![vec![], vec![1]]));
+        // cycle unreachable from 0
+        let graph = vec![vec![], vec![2], vec![3], vec![1]];
+        assert!(has_cycle_directed(&graph));
+    }
+
+    fn is_topological_order(graph: &[Vec<usize>], order: &[usize]) -> bool {
+        let mut pos = vec![usize::MAX; graph.len()];
+        for (i, &v) in order.iter().enumerate() {
+            pos[v] = i;
+        }
+        order.len() == graph.len()
+            && graph
+                .iter()
+                .enumerate()
+                .all(|(u, edges)| edges.iter().all(|&v| pos[u] < pos[v]))
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        // 5 -> 2 -> 3 -> 1, 5 -> 0, 4 -> 0, 4 -> 1
+        let graph = vec![vec![], vec![], vec![3], vec![1], vec![0, 1], vec![2, 0]];
+        let order = topological_sort(&graph).unwrap();
+        assert_eq!(order, vec![4, 5, 2, 0, 3, 1]);
+        assert!(is_topological_order(&graph, &order));
+
+        assert_eq!(topological_sort(&vec![vec![], vec![]]), Some(vec![0, 1]));
+        assert_eq!(topological_sort(&vec![]), Some(vec![]));
+    }
+
+    #[test]
+    fn test_topological_sort_with_cycle() {
+        let graph = vec![vec![1], vec![2], vec![3], vec![1]];
+        assert_eq!(topological_sort(&graph), None);
+        assert_eq!(topological_sort(&vec![vec![0]]), None);
+    }
+
+    #[test]
+    fn test_two_coloring() {
+        // even cycle 0 - 1 - 2 - 3 - 0
+        let graph = vec![vec![1, 3], vec![0, 2], vec![1, 3], vec![2, 0]];
+        assert_eq!(two_coloring(&graph), Some(vec![0, 1, 0, 1]));
+
+        // odd cycle 0 - 1 - 2 - 0
+        let graph = vec![vec![1, 2], vec![0, 2], vec![0, 1]];
+        assert_eq!(two_coloring(&graph), None);
+
+        // forest
+        let graph = create_graph();
+        assert_eq!(two_coloring(&graph), Some(vec![0, 1, 1, 0, 0, 0, 0]));
+        let graph = vec![vec![1], vec![0], vec![3], vec![2], vec![]];
+        assert_eq!(two_coloring(&graph), Some(vec![0, 1, 0, 1, 0]));
+
+        // odd cycle only in the second component
+        let graph = vec![vec![1], vec![0], vec![3, 4], vec![2, 4], vec![2, 3]];
+        assert_eq!(two_coloring(&graph), None);
+    }
+
+    fn subtree_sizes_recursive(graph: &Vec<Vec<usize>>, v: usize, p: usize, size: &mut Vec<usize>) {
+        size[v] = 1;
+        for &next_v in &graph[v] {
+            if next_v != p {
+                subtree_sizes_recursive(graph, next_v, v, size);
+                size[v] += size[next_v];
+            }
+        }
+    }
+
+    #[test]
+    fn test_subtree_sizes() {
+        let graph = create_graph();
+        assert_eq!(subtree_sizes(&graph, 0), vec![7, 3, 3, 1, 1, 1, 1]);
+        assert_eq!(subtree_sizes(&graph, 3), vec![4, 6, 3, 7, 1, 1, 1]);
+
+        // balanced binary tree with 15 nodes
+        let n = 15;
+        let mut graph = vec![vec![]; n];
+        for v in 1..n {
+            graph[v].push((v - 1) / 2);
+            graph[(v - 1) / 2].push(v);
+        }
+        for root in [0, 7, 10] {
+            let mut expected = vec![0; n];
+            subtree_sizes_recursive(&graph, root, usize::MAX, &mut expected);
+            assert_eq!(subtree_sizes(&graph, root), expected);
+        }
+        assert_eq!(subtree_sizes(&vec![vec![]], 0), vec![1]);
+    }
+
+    #[test]
+    fn test_subtree_sizes_path() {
+        let n = 1000;
+        let mut graph = vec![vec![]; n];
+        for i in 0..n - 1 {
+            graph[i].push(i + 1);
+            graph[i + 1].push(i);
+        }
+        let mut expected = vec![0; n];
+        subtree_sizes_recursive(&graph, 0, usize::MAX, &mut expected);
+        assert_eq!(subtree_sizes(&graph, 0), expected);
+        assert_eq!(subtree_sizes(&graph, n - 1), (1..=n).collect::<Vec<_>>());
+
+        // deep path that would overflow a recursive implementation
+        let n = 200_000;
+        let mut graph = vec![vec![]; n];
+        for i in 0..n - 1 {
+            graph[i].push(i + 1);
+            graph[i + 1].push(i);
+        }
+        let size = subtree_sizes(&graph, 0);
+        assert_eq!(size[0], n);
+        assert_eq!(size[n - 1], 1);
+    }
 }
@@ -1,21 +1,59 @@
 use cargo_snippet::snippet;
 
+// 辺のコストとして使える型
+// zero() は始点の距離、inf() は未到達を表す番兵です。コストは非負である必要があります。
+#[snippet("dijkstra")]
+pub trait DijkstraCost: Copy + Ord + std::ops::Add<Output = Self> {
+    fn zero() -> Self;
+    fn inf() -> Self;
+}
+
+#[snippet("dijkstra")]
+impl DijkstraCost for usize {
+    fn zero() -> Self {
+        0
+    }
+    fn inf() -> Self {
+        usize::MAX
+    }
+}
+
+#[snippet("dijkstra")]
+impl DijkstraCost for u64 {
+    fn zero() -> Self {
+        0
+    }
+    fn inf() -> Self {
+        u64::MAX
+    }
+}
+
+#[snippet("dijkstra")]
+impl DijkstraCost for i64 {
+    fn zero() -> Self {
+        0
+    }
+    fn inf() -> Self {
+        i64::MAX
+    }
+}
+
 #[snippet("dijkstra")]
 #[derive(Debug, Clone, Eq, PartialEq)]
-struct Node {
+struct Node<C> {
     vertex: usize,
-    cost: usize,
+    cost: C,
 }
 
 #[snippet("dijkstra")]
-impl Ord for Node {
+impl<C: Ord> Ord for Node<C> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         other.cost.cmp(&self.cost)
     }
 }
 
 #[snippet("dijkstra")]
-impl PartialOrd for Node {
+impl<C: Ord> PartialOrd for Node<C> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
     }
@@ -23,8 +61,182 @@ impl PartialOrd for Node {
 
 #[snippet("dijkstra")]
 pub fn dijkstra(graph: &[Vec<(usize, usize)>], start: usize) -> Vec<usize> {
+    dijkstra_generic(graph, start)
+}
+
+#[snippet("dijkstra")]
+pub fn dijkstra_generic<C: DijkstraCost>(graph: &[Vec<(usize, C)>], start: usize) -> Vec<C> {
+    dijkstra_until(graph, start, None)
+}
+
+// target が指定されていれば、target がヒープから取り出された時点で打ち切ります。
+// このとき dist[target] は確定していますが、他の頂点の値は確定しているとは限りません。
+#[snippet("dijkstra")]
+fn dijkstra_until<C: DijkstraCost>(
+    graph: &[Vec<(usize, C)>],
+    start: usize,
+    target: Option<usize>,
+) -> Vec<C> {
+    let n = graph.len();
+    let mut dist = vec![C::inf(); n];
+    let mut pq = std::collections::BinaryHeap::new();
+
+    dist[start] = C::zero();
+    pq.push(Node {
+        vertex: start,
+        cost: C::zero(),
+    });
+
+    while let Some(Node { vertex, cost }) = pq.pop() {
+        if dist[vertex] < cost {
+            continue;
+        }
+        if target == Some(vertex) {
+            break;
+        }
+
+        for &(next_vertex, edge_cost) in &graph[vertex] {
+            let new_cost = cost + edge_cost;
+            if new_cost < dist[next_vertex] {
+                dist[next_vertex] = new_cost;
+                pq.push(Node {
+                    vertex: next_vertex,
+                    cost: new_cost,
+                });
+            }
+        }
+    }
+
+    dist
+}
+
+#[snippet(include = "dijkstra")]
+pub fn dijkstra_to(graph: &[Vec<(usize, usize)>], start: usize, target: usize) -> Option<usize> {
+    let dist = dijkstra_until(graph, start, Some(target));
+    if dist[target] == usize::MAX {
+        None
+    } else {
+        Some(dist[target])
+    }
+}
+
+// マス (x, y) に入るたびに cost[x][y] がかかるグリッド上のダイクストラ法
+// 頂点番号を x * w + y として Node を使い回します。始点のコストはかかりません。
+#[snippet(include = "dijkstra")]
+pub fn grid_dijkstra(
+    cost: &Vec<Vec<usize>>,
+    start: (usize, usize),
+    passable: impl Fn(usize) -> bool,
+) -> Vec<Vec<usize>> {
+    let dx: [i32; 4] = [1, 0, -1, 0];
+    let dy: [i32; 4] = [0, 1, 0, -1];
+
+    if cost.is_empty() {
+        return Vec::new();
+    }
+
+    let h = cost.len();
+    let w = cost[0].len();
+    let mut dist = vec![vec![usize::MAX; w]; h];
+    let mut pq = std::collections::BinaryHeap::new();
+
+    if !passable(cost[start.0][start.1]) {
+        return dist;
+    }
+
+    dist[start.0][start.1] = 0;
+    pq.push(Node {
+        vertex: start.0 * w + start.1,
+        cost: 0,
+    });
+
+    while let Some(Node { vertex, cost: d }) = pq.pop() {
+        let (x, y) = (vertex / w, vertex % w);
+        if dist[x][y] < d {
+            continue;
+        }
+
+        for dir in 0..4 {
+            let nx = x as i32 + dx[dir];
+            let ny = y as i32 + dy[dir];
+
+            if nx < 0 || h as i32 <= nx || ny < 0 || w as i32 <= ny {
+                continue;
+            }
+
+            let nx = nx as usize;
+            let ny = ny as usize;
+
+            if !passable(cost[nx][ny]) {
+                continue;
+            }
+
+            let new_cost = d + cost[nx][ny];
+            if new_cost < dist[nx][ny] {
+                dist[nx][ny] = new_cost;
+                pq.push(Node {
+                    vertex: nx * w + ny,
+                    cost: new_cost,
+                });
+            }
+        }
+    }
+
+    dist
+}
+
+// 始点から target までの経路（同じ頂点を複数回通ってもよい）のコストを小さい順に k 個求めます。
+// 各頂点をヒープから k 回まで取り出してよいとするダイクストラ法です。経路が k 個未満ならその分だけ返します。
+// 計算量：O(k M log(k M))
+#[snippet(include = "dijkstra")]
+pub fn k_shortest_distances(
+    graph: &[Vec<(usize, usize)>],
+    start: usize,
+    target: usize,
+    k: usize,
+) -> Vec<usize> {
+    let n = graph.len();
+    let mut count = vec![0; n];
+    let mut result = Vec::new();
+    let mut pq = std::collections::BinaryHeap::new();
+
+    pq.push(Node {
+        vertex: start,
+        cost: 0,
+    });
+
+    while let Some(Node { vertex, cost }) = pq.pop() {
+        if count[vertex] >= k {
+            continue;
+        }
+        count[vertex] += 1;
+
+        if vertex == target {
+            result.push(cost);
+            if result.len() == k {
+                break;
+            }
+        }
+
+        for &(next_vertex, edge_cost) in &graph[vertex] {
+            if count[next_vertex] < k {
+                pq.push(Node {
+                    vertex: next_vertex,
+                    cost: cost + edge_cost,
+                });
+            }
+        }
+    }
+
+    result
+}
+
+// prev[v] は最短経路木での v の親（始点と到達不能な頂点は usize::MAX）
+#[snippet(include = "dijkstra")]
+pub fn dijkstra_with_prev(graph: &[Vec<(usize, usize)>], start: usize) -> (Vec<usize>, Vec<usize>) {
     let n = graph.len();
-    let mut dist = vec![std::usize::MAX; n];
+    let mut dist = vec![usize::MAX; n];
+    let mut prev = vec![usize::MAX; n];
     let mut pq = std::collections::BinaryHeap::new();
 
     dist[start] = 0;
@@ -42,6 +254,7 @@ pub fn dijkstra(graph: &[Vec<(usize, usize)>], start: usize) -> Vec<usize> {
             let new_cost = cost + edge_cost;
             if new_cost < dist[next_vertex] {
                 dist[next_vertex] = new_cost;
+                prev[next_vertex] = vertex;
                 pq.push(Node {
                     vertex: next_vertex,
                     cost: new_cost,
@@ -50,7 +263,25 @@ pub fn dijkstra(graph: &[Vec<(usize, usize)>], start: usize) -> Vec<usize> {
         }
     }
 
-    dist
+    (dist, prev)
+}
+
+// prev をたどって始点から target までの経路を復元します。
+// 始点と到達不能な頂点はどちらも prev が usize::MAX なので、target がそれらの場合は None を返します。
+#[snippet]
+pub fn restore_path(prev: &[usize], target: usize) -> Option<Vec<usize>> {
+    if prev[target] == usize::MAX {
+        return None;
+    }
+
+    let mut path = vec![target];
+    let mut v = target;
+    while prev[v] != usize::MAX {
+        v = prev[v];
+        path.push(v);
+    }
+    path.reverse();
+    Some(path)
 }
 
 #[cfg(test)]
@@ -86,4 +317,153 @@ mod tests {
         let expected = vec![5, 3, 1, 0];
         assert_eq!(dist, expected);
     }
+
+    #[test]
+    fn test_dijkstra_with_prev() {
+        let graph = vec![
+            vec![(1, 2), (2, 5)],
+            vec![(0, 2), (3, 3)],
+            vec![(0, 5), (3, 1)],
+            vec![(1, 3), (2, 1)],
+        ];
+
+        let (dist, prev) = dijkstra_with_prev(&graph, 0);
+        assert_eq!(dist, dijkstra(&graph, 0));
+        assert_eq!(prev[0], usize::MAX);
+        assert_eq!(restore_path(&prev, 1), Some(vec![0, 1]));
+        assert_eq!(restore_path(&prev, 3), Some(vec![0, 1, 3]));
+        assert_eq!(restore_path(&prev, 0), None);
+
+        let (dist, prev) = dijkstra_with_prev(&graph, 1);
+        let path = restore_path(&prev, 2).unwrap();
+        assert_eq!(path, vec![1, 3, 2]);
+        let cost: usize = path
+            .windows(2)
+            .map(|e| graph[e[0]].iter().find(|&&(v, _)| v == e[1]).unwrap().1)
+            .sum();
+        assert_eq!(cost, dist[2]);
+    }
+
+    #[test]
+    fn test_dijkstra_with_prev_unreachable() {
+        let graph = vec![vec![(1, 4)], vec![], vec![(0, 1)]];
+        let (dist, prev) = dijkstra_with_prev(&graph, 0);
+        assert_eq!(dist, vec![0, 4, usize::MAX]);
+        assert_eq!(restore_path(&prev, 1), Some(vec![0, 1]));
+        assert_eq!(restore_path(&prev, 2), None);
+    }
+
+    #[test]
+    fn test_dijkstra_to() {
+        let graph = vec![
+            vec![(1, 2), (2, 5)],
+            vec![(0, 2), (3, 3)],
+            vec![(0, 5), (3, 1)],
+            vec![(1, 3), (2, 1)],
+        ];
+
+        for start in 0..graph.len() {
+            let dist = dijkstra(&graph, start);
+            for (target, &d) in dist.iter().enumerate() {
+                assert_eq!(dijkstra_to(&graph, start, target), Some(d));
+            }
+        }
+
+        let graph = vec![vec![(1, 4)], vec![(0, 4)], vec![]];
+        assert_eq!(dijkstra_to(&graph, 0, 1), Some(4));
+        assert_eq!(dijkstra_to(&graph, 0, 2), None);
+        assert_eq!(dijkstra_to(&graph, 2, 2), Some(0));
+    }
+
+    #[test]
+    fn test_dijkstra_generic_u64() {
+        let big = 1u64 << 40;
+        let graph: Vec<Vec<(usize, u64)>> = vec![
+            vec![(1, big), (2, 3 * big)],
+            vec![(2, big)],
+            vec![(3, 5)],
+            vec![],
+        ];
+        let dist = dijkstra_generic(&graph, 0);
+        assert_eq!(dist, vec![0, big, 2 * big, 2 * big + 5]);
+
+        let dist = dijkstra_generic(&graph, 2);
+        assert_eq!(dist, vec![u64::MAX, u64::MAX, 0, 5]);
+    }
+
+    #[test]
+    fn test_dijkstra_generic_matches_usize() {
+        let graph = vec![
+            vec![(1, 2), (2, 5)],
+            vec![(0, 2), (3, 3)],
+            vec![(0, 5), (3, 1)],
+            vec![(1, 3), (2, 1)],
+        ];
+        let graph_i64: Vec<Vec<(usize, i64)>> = graph
+            .iter()
+            .map(|edges| edges.iter().map(|&(v, c)| (v, c as i64)).collect())
+            .collect();
+
+        for start in 0..graph.len() {
+            let expected: Vec<i64> = dijkstra(&graph, start)
+                .into_iter()
+                .map(|d| d as i64)
+                .collect();
+            assert_eq!(dijkstra_generic(&graph_i64, start), expected);
+        }
+    }
+
+    #[test]
+    fn test_grid_dijkstra() {
+        let cost = vec![vec![1, 9, 1], vec![1, 9, 1], vec![1, 1, 1]];
+        let dist = grid_dijkstra(&cost, (0, 0), |_| true);
+        // Going around the expensive column is cheaper than crossing it
+        assert_eq!(dist, vec![vec![0, 9, 6], vec![1, 10, 5], vec![2, 3, 4]]);
+    }
+
+    #[test]
+    fn test_grid_dijkstra_impassable() {
+        let inf = usize::MAX;
+        let cost = vec![vec![1, 0, 1], vec![1, 0, 1], vec![1, 1, 1]];
+        let dist = grid_dijkstra(&cost, (0, 0), |c| c > 0);
+        assert_eq!(dist, vec![vec![0, inf, 6], vec![1, inf, 5], vec![2, 3, 4]]);
+
+        let dist = grid_dijkstra(&cost, (0, 1), |c| c > 0);
+        assert_eq!(dist, vec![vec![inf; 3]; 3]);
+    }
+
+    #[test]
+    fn test_k_shortest_distances() {
+        // Multigraph with two parallel edges 0 -> 1
+        let graph = vec![
+            vec![(1, 1), (1, 2), (2, 5)],
+            vec![(2, 1), (3, 4)],
+            vec![(3, 1)],
+            vec![],
+        ];
+        assert_eq!(k_shortest_distances(&graph, 0, 2, 1), vec![2]);
+        assert_eq!(k_shortest_distances(&graph, 0, 2, 3), vec![2, 3, 5]);
+        assert_eq!(k_shortest_distances(&graph, 0, 3, 4), vec![3, 4, 5, 6]);
+
+        // Fewer than k paths
+        assert_eq!(k_shortest_distances(&graph, 0, 2, 10), vec![2, 3, 5]);
+        assert_eq!(k_shortest_distances(&graph, 3, 0, 2), vec![]);
+        assert_eq!(k_shortest_distances(&graph, 0, 0, 2), vec![0]);
+    }
+
+    #[test]
+    fn test_k_shortest_distances_with_cycle() {
+        // 0 <-> 1 with cost 1; walks may revisit vertices
+        let graph = vec![vec![(1, 1)], vec![(0, 1)]];
+        assert_eq!(k_shortest_distances(&graph, 0, 1, 3), vec![1, 3, 5]);
+
+        let graph = vec![
+            vec![(1, 2), (2, 5)],
+            vec![(0, 2), (3, 3)],
+            vec![(0, 5), (3, 1)],
+            vec![(1, 3), (2, 1)],
+        ];
+        let dist = dijkstra(&graph, 0);
+        assert_eq!(k_shortest_distances(&graph, 0, 3, 1), vec![dist[3]]);
+    }
 }
@@ -0,0 +1,80 @@
+use cargo_snippet::snippet;
+
+// 長さ k の各区間 a[i..i+k] に含まれる値の種類数を返します。
+// 区間をずらすたびに出入りする値の個数を HashMap で管理するので O(n) です。
+// k == 0 または k > a.len() のときは空の Vec を返します。
+#[snippet]
+pub fn distinct_in_windows(a: &[i64], k: usize) -> Vec<usize> {
+    if k == 0 || k > a.len() {
+        return vec![];
+    }
+
+    let mut count = std::collections::HashMap::new();
+    let mut result = Vec::with_capacity(a.len() - k + 1);
+    for (i, &x) in a.iter().enumerate() {
+        *count.entry(x).or_insert(0usize) += 1;
+
+        if i >= k {
+            let out = a[i - k];
+            let c = count.get_mut(&out).unwrap();
+            *c -= 1;
+            if *c == 0 {
+                count.remove(&out);
+            }
+        }
+
+        if i + 1 >= k {
+            result.push(count.len());
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn naive(a: &[i64], k: usize) -> Vec<usize> {
+        if k == 0 || k > a.len() {
+            return vec![];
+        }
+        a.windows(k)
+            .map(|w| w.iter().collect::<HashSet<_>>().len())
+            .collect()
+    }
+
+    #[test]
+    fn test_distinct_in_windows() {
+        let a = vec![1, 2, 1, 3, 4, 2, 3];
+        assert_eq!(distinct_in_windows(&a, 4), vec![3, 4, 4, 3]);
+        assert_eq!(distinct_in_windows(&a, 1), vec![1; 7]);
+        assert_eq!(distinct_in_windows(&a, 7), vec![4]);
+        assert_eq!(distinct_in_windows(&[5, 5, 5], 2), vec![1, 1]);
+        assert_eq!(distinct_in_windows(&[-1, 1, -1], 2), vec![2, 2]);
+    }
+
+    #[test]
+    fn test_distinct_in_windows_out_of_range() {
+        assert_eq!(distinct_in_windows(&[1, 2, 3], 4), vec![]);
+        assert_eq!(distinct_in_windows(&[1, 2, 3], 0), vec![]);
+        assert_eq!(distinct_in_windows(&[], 1), vec![]);
+    }
+
+    #[test]
+    fn test_distinct_in_windows_against_naive() {
+        // small deterministic pseudo-random sequence
+        let mut x: u64 = 12345;
+        let a: Vec<i64> = (0..200)
+            .map(|_| {
+                x = x
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (x >> 60) as i64 - 8
+            })
+            .collect();
+        for k in 0..=a.len() + 1 {
+            assert_eq!(distinct_in_windows(&a, k), naive(&a, k));
+        }
+    }
+}
@@ -2,8 +2,13 @@ use cargo_snippet::snippet;
 
 #[snippet]
 pub fn doubling(next_nodes: &Vec<usize>, node: usize, k: usize) -> usize {
+    if k == 0 {
+        return node;
+    }
+
     let n = next_nodes.len();
-    let max_log = (k as f64).log2().ceil() as usize;
+    // k の最上位ビットまで使えるよう、k を2進数で表すのに必要なビット数だけ用意します。
+    let max_log = (usize::BITS - k.leading_zeros()) as usize - 1;
     let mut doubling_table = vec![vec![0; n]; max_log + 1];
 
     // 初期化します。iから始まる1ステップ先はnext_nodes[i]です。
@@ -28,6 +33,191 @@ pub fn doubling(next_nodes: &Vec<usize>, node: usize, k: usize) -> usize {
     current_node
 }
 
+// 何度も (node, k) を問い合わせる場合は、テーブルを一度だけ構築して使い回します。
+#[snippet("Doubling")]
+pub struct Doubling {
+    table: Vec<Vec<usize>>,
+}
+
+#[snippet("Doubling")]
+impl Doubling {
+    // max_k 以下の k について query できるテーブルを構築します。
+    pub fn new(next_nodes: &[usize], max_k: usize) -> Self {
+        let n = next_nodes.len();
+        // max_k を2進数で表すのに必要なビット数
+        let log = (usize::BITS - max_k.leading_zeros()).max(1) as usize;
+        let mut table = vec![vec![0; n]; log];
+        table[0].copy_from_slice(next_nodes);
+
+        for i in 1..log {
+            for j in 0..n {
+                table[i][j] = table[i - 1][table[i - 1][j]];
+            }
+        }
+
+        Doubling { table }
+    }
+
+    // node から k ステップ進んだ先のノードを O(log k) で返します。
+    pub fn query(&self, node: usize, k: usize) -> usize {
+        debug_assert!(
+            (usize::BITS - k.leading_zeros()) as usize <= self.table.len(),
+            "k exceeds the max_k given to Doubling::new"
+        );
+
+        let mut current_node = node;
+        for (i, i_steps_nodes) in self.table.iter().enumerate() {
+            if (k >> i) & 1 == 1 {
+                current_node = i_steps_nodes[current_node];
+            }
+        }
+        current_node
+    }
+}
+
+// 行き先に加えて、k ステップの間に通ったノードの値をモノイド (op, identity) で畳み込みます。
+// values[j] は j から1ステップ進むときに加わる値です。
+// 畳み込みは経路の順に行うので、op は可換でなくても構いません。
+#[snippet("DoublingWithAgg")]
+pub struct DoublingWithAgg<T, F> {
+    to: Vec<Vec<usize>>,
+    val: Vec<Vec<T>>,
+    identity: T,
+    op: F,
+}
+
+#[snippet("DoublingWithAgg")]
+impl<T: Clone, F: Fn(&T, &T) -> T> DoublingWithAgg<T, F> {
+    pub fn new(next_nodes: &[usize], values: &[T], max_k: usize, identity: T, op: F) -> Self {
+        let n = next_nodes.len();
+        let log = (usize::BITS - max_k.leading_zeros()).max(1) as usize;
+        let mut to = vec![next_nodes.to_vec()];
+        let mut val = vec![values.to_vec()];
+
+        // 2^i ステップ = 2^(i-1) ステップ + 2^(i-1) ステップ
+        for i in 1..log {
+            let mut to_i = vec![0; n];
+            let mut val_i = Vec::with_capacity(n);
+            for j in 0..n {
+                let mid = to[i - 1][j];
+                to_i[j] = to[i - 1][mid];
+                val_i.push(op(&val[i - 1][j], &val[i - 1][mid]));
+            }
+            to.push(to_i);
+            val.push(val_i);
+        }
+
+        DoublingWithAgg {
+            to,
+            val,
+            identity,
+            op,
+        }
+    }
+
+    // node から k ステップ進んだ先のノードと、その間の値の畳み込みを返します。
+    pub fn query(&self, node: usize, k: usize) -> (usize, T) {
+        debug_assert!(
+            (usize::BITS - k.leading_zeros()) as usize <= self.to.len(),
+            "k exceeds the max_k given to DoublingWithAgg::new"
+        );
+
+        let mut current_node = node;
+        let mut agg = self.identity.clone();
+        for i in 0..self.to.len() {
+            if (k >> i) & 1 == 1 {
+                agg = (self.op)(&agg, &self.val[i][current_node]);
+                current_node = self.to[i][current_node];
+            }
+        }
+        (current_node, agg)
+    }
+}
+
+// ダブリングで親を 2^k 個たどる表 up[k][v] を持ち、LCA と k 個上の祖先を O(log n) で求めます。
+// 根の親は根自身とします。
+#[snippet("Lca")]
+pub struct Lca {
+    up: Vec<Vec<usize>>,
+    depth: Vec<usize>,
+}
+
+#[snippet("Lca")]
+impl Lca {
+    // graph は無向の木の隣接リストです。
+    pub fn new(graph: &Vec<Vec<usize>>, root: usize) -> Self {
+        let n = graph.len();
+        let log = (usize::BITS - n.leading_zeros()).max(1) as usize;
+        let mut parent = vec![root; n];
+        let mut depth = vec![0; n];
+
+        // 再帰を避けるためスタックで親と深さを求めます。
+        let mut seen = vec![false; n];
+        let mut stack = vec![root];
+        seen[root] = true;
+        while let Some(v) = stack.pop() {
+            for &next_v in &graph[v] {
+                if seen[next_v] {
+                    continue;
+                }
+                seen[next_v] = true;
+                parent[next_v] = v;
+                depth[next_v] = depth[v] + 1;
+                stack.push(next_v);
+            }
+        }
+
+        let mut up = vec![parent];
+        for i in 1..log {
+            let next = (0..n).map(|v| up[i - 1][up[i - 1][v]]).collect();
+            up.push(next);
+        }
+
+        Lca { up, depth }
+    }
+
+    pub fn depth(&self, v: usize) -> usize {
+        self.depth[v]
+    }
+
+    // v から k 個上の祖先を返します。根を越える場合は None です。
+    pub fn kth_ancestor(&self, v: usize, k: usize) -> Option<usize> {
+        if k > self.depth[v] {
+            return None;
+        }
+        let mut v = v;
+        for (i, up_i) in self.up.iter().enumerate() {
+            if (k >> i) & 1 == 1 {
+                v = up_i[v];
+            }
+        }
+        Some(v)
+    }
+
+    pub fn lca(&self, u: usize, v: usize) -> usize {
+        let (mut u, mut v) = if self.depth[u] >= self.depth[v] {
+            (u, v)
+        } else {
+            (v, u)
+        };
+
+        // 深い方を同じ深さまで持ち上げます。
+        u = self.kth_ancestor(u, self.depth[u] - self.depth[v]).unwrap();
+        if u == v {
+            return u;
+        }
+
+        // 祖先が一致しない範囲で大きいステップから持ち上げます。
+        for up_i in self.up.iter().rev() {
+            if up_i[u] != up_i[v] {
+                u = up_i[u];
+                v = up_i[v];
+            }
+        }
+        self.up[0][u]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -57,4 +247,165 @@ mod tests {
         assert_eq!(doubling(&next_nodes, 0, 5), 0);
         assert_eq!(doubling(&next_nodes, 0, 6), 1);
     }
+
+    #[test]
+    fn test_doubling_struct() {
+        let cases = vec![vec![0], vec![1, 0], vec![1, 2, 3, 4, 0], vec![1, 2, 0, 2]];
+        for next_nodes in cases {
+            let max_k = 100;
+            let db = Doubling::new(&next_nodes, max_k);
+            for node in 0..next_nodes.len() {
+                let mut expected = node;
+                for k in 0..=max_k {
+                    assert_eq!(db.query(node, k), expected);
+                    expected = next_nodes[expected];
+                }
+            }
+            for node in 0..next_nodes.len() {
+                for k in 1..=max_k {
+                    assert_eq!(db.query(node, k), doubling(&next_nodes, node, k));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_doubling_struct_power_of_two_max_k() {
+        let next_nodes = vec![1, 2, 3, 4, 0];
+        let db = Doubling::new(&next_nodes, 8);
+        assert_eq!(db.query(0, 8), 3);
+        assert_eq!(db.query(2, 7), 4);
+        assert_eq!(db.query(4, 0), 4);
+    }
+
+    #[test]
+    fn test_doubling_with_agg_sum_on_cycle() {
+        let next_nodes = vec![1, 2, 3, 4, 0]; // 0 -> 1 -> 2 -> 3 -> 4 -> 0
+        let weights = vec![1, 10, 100, 1000, 10000];
+        let db = DoublingWithAgg::new(&next_nodes, &weights, 20, 0, |a: &usize, b: &usize| a + b);
+
+        assert_eq!(db.query(0, 0), (0, 0));
+        assert_eq!(db.query(0, 3), (3, 111));
+        assert_eq!(db.query(3, 4), (2, 11011));
+        assert_eq!(db.query(0, 5), (0, 11111));
+        assert_eq!(db.query(2, 12), (4, 22222 + 100 + 1000));
+
+        for node in 0..next_nodes.len() {
+            let mut expected = (node, 0);
+            for k in 0..=20 {
+                assert_eq!(db.query(node, k), expected);
+                expected = (next_nodes[expected.0], expected.1 + weights[expected.0]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_doubling_with_agg_keeps_path_order() {
+        // 非可換な演算（文字列の連結）でも経路の順に畳み込まれることを確認します。
+        let next_nodes = vec![1, 2, 0];
+        let labels = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let db = DoublingWithAgg::new(
+            &next_nodes,
+            &labels,
+            10,
+            String::new(),
+            |a: &String, b: &String| format!("{}{}", a, b),
+        );
+        assert_eq!(db.query(1, 7), (2, "bcabcab".to_string()));
+        assert_eq!(db.query(2, 0), (2, String::new()));
+    }
+
+    fn create_tree() -> Vec<Vec<usize>> {
+        //         0
+        //       /   \
+        //      1     2
+        //     / \     \
+        //    3   4     5
+        //   /         / \
+        //  6         7   8
+        let edges = [
+            (0, 1),
+            (0, 2),
+            (1, 3),
+            (1, 4),
+            (2, 5),
+            (3, 6),
+            (5, 7),
+            (5, 8),
+        ];
+        let mut graph = vec![vec![]; 9];
+        for &(a, b) in &edges {
+            graph[a].push(b);
+            graph[b].push(a);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_lca() {
+        let lca = Lca::new(&create_tree(), 0);
+        // siblings
+        assert_eq!(lca.lca(3, 4), 1);
+        assert_eq!(lca.lca(7, 8), 5);
+        // cousins
+        assert_eq!(lca.lca(6, 4), 1);
+        assert_eq!(lca.lca(6, 7), 0);
+        assert_eq!(lca.lca(4, 5), 0);
+        // ancestor and descendant
+        assert_eq!(lca.lca(1, 6), 1);
+        assert_eq!(lca.lca(8, 2), 2);
+        assert_eq!(lca.lca(0, 7), 0);
+        assert_eq!(lca.lca(6, 6), 6);
+    }
+
+    #[test]
+    fn test_lca_kth_ancestor() {
+        let lca = Lca::new(&create_tree(), 0);
+        assert_eq!(lca.depth(6), 3);
+        assert_eq!(lca.kth_ancestor(6, 0), Some(6));
+        assert_eq!(lca.kth_ancestor(6, 1), Some(3));
+        assert_eq!(lca.kth_ancestor(6, 2), Some(1));
+        assert_eq!(lca.kth_ancestor(6, 3), Some(0));
+        assert_eq!(lca.kth_ancestor(6, 4), None);
+        assert_eq!(lca.kth_ancestor(0, 1), None);
+        assert_eq!(lca.kth_ancestor(8, 2), Some(2));
+    }
+
+    #[test]
+    fn test_lca_long_path() {
+        // 0 - 1 - 2 - ... - 99 の長いパスを 50 を根として扱います。
+        let n = 100;
+        let mut graph = vec![vec![]; n];
+        for i in 0..n - 1 {
+            graph[i].push(i + 1);
+            graph[i + 1].push(i);
+        }
+        let lca = Lca::new(&graph, 50);
+        assert_eq!(lca.lca(0, 49), 49);
+        assert_eq!(lca.lca(10, 90), 50);
+        assert_eq!(lca.lca(99, 70), 70);
+        assert_eq!(lca.kth_ancestor(0, 50), Some(50));
+        assert_eq!(lca.kth_ancestor(0, 51), None);
+        assert_eq!(lca.kth_ancestor(99, 37), Some(62));
+    }
+
+    #[test]
+    fn test_doubling_small_and_huge_k() {
+        let next_nodes = vec![1, 2, 3, 4, 0]; // 0 -> 1 -> 2 -> 3 -> 4 -> 0
+        assert_eq!(doubling(&next_nodes, 2, 0), 2);
+        assert_eq!(doubling(&next_nodes, 2, 1), 3);
+        for i in 0..8 {
+            assert_eq!(doubling(&next_nodes, 0, 1 << i), (1 << i) % 5);
+        }
+
+        // 2^(BITS-1) ≡ 3, usize::MAX ≡ 0 (mod 5) for both 32 and 64 bit usize
+        let top_bit = 1usize << (usize::BITS - 1);
+        assert_eq!(doubling(&next_nodes, 0, top_bit), 3);
+        assert_eq!(doubling(&next_nodes, 0, usize::MAX), 0);
+        assert_eq!(Doubling::new(&next_nodes, usize::MAX).query(0, top_bit), 3);
+        assert_eq!(
+            Doubling::new(&next_nodes, usize::MAX).query(1, usize::MAX),
+            1
+        );
+    }
 }
@@ -0,0 +1,48 @@
+use cargo_snippet::snippet;
+
+// 編集距離（レーベンシュタイン距離）
+// 挿入・削除・置換をそれぞれコスト 1 として、a を b に変える最小コストを求めます。
+// dp[i][j] = a[..i] を b[..j] に変える最小コスト
+//   dp[i][j] = min(dp[i-1][j] + 1, dp[i][j-1] + 1, dp[i-1][j-1] + (a[i-1] != b[j-1]))
+// dp[i] は dp[i-1] だけから求まるので、2 行分だけ持ちます。O(|a||b|) 時間、O(|b|) 空間
+#[snippet]
+pub fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+
+    for (i, &ca) in a.iter().enumerate() {
+        cur[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let replace = prev[j] + usize::from(ca != cb);
+            cur[j + 1] = replace.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars("sitting"), &chars("kitten")), 3);
+        assert_eq!(edit_distance(&chars("flaw"), &chars("lawn")), 2);
+        assert_eq!(edit_distance(&chars("intention"), &chars("execution")), 5);
+        assert_eq!(edit_distance(&chars("abc"), &chars("abc")), 0);
+    }
+
+    #[test]
+    fn test_edit_distance_empty() {
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+        assert_eq!(edit_distance(&chars("abcd"), &chars("")), 4);
+        assert_eq!(edit_distance(&chars(""), &chars("")), 0);
+    }
+}
@@ -1,63 +1,202 @@
 use cargo_snippet::snippet;
 
 #[snippet("find_centroid")]
-fn subtree_dfs(
-    graph: &Vec<Vec<usize>>,
-    weight: &Vec<usize>,
-    subtree_weight: &mut Vec<usize>,
-    v: usize,
-    par: usize,
-) -> usize {
-    subtree_weight[v] = weight[v];
-    for &next_v in &graph[v] {
-        if next_v == par {
-            continue;
+// 再帰を使わずに帰りがけ順（子は隣接リストの順）を order に、親を parent に書き込みます。根の親は n です。
+// removed[v] が true の頂点は通りません。
+// 重心分解で何度も呼べるように、バッファは呼び出し側で確保し、訪れた頂点だけを触ります。
+fn post_order(
+    graph: &[Vec<usize>],
+    root: usize,
+    removed: &[bool],
+    order: &mut Vec<usize>,
+    parent: &mut [usize],
+    stack: &mut Vec<(usize, usize, usize)>,
+) {
+    let n = graph.len();
+    order.clear();
+    stack.clear();
+    stack.push((root, n, 0));
+
+    while let Some((v, par, i)) = stack.pop() {
+        if i < graph[v].len() {
+            stack.push((v, par, i + 1));
+            let next_v = graph[v][i];
+            if next_v != par && !removed[next_v] {
+                stack.push((next_v, v, 0));
+            }
+        } else {
+            parent[v] = par;
+            order.push(v);
         }
-        subtree_weight[v] += subtree_dfs(graph, weight, subtree_weight, next_v, v);
     }
-    subtree_weight[v]
 }
 
 #[snippet("find_centroid")]
-fn centroid_dfs(
+fn subtree_weights(
     n: usize,
-    graph: &Vec<Vec<usize>>,
+    weight: &[usize],
+    subtree_weight: &mut [usize],
+    order: &[usize],
+    parent: &[usize],
+) {
+    for &v in order {
+        subtree_weight[v] = weight[v];
+    }
+    for &v in order {
+        if parent[v] != n {
+            subtree_weight[parent[v]] += subtree_weight[v];
+        }
+    }
+}
+
+#[snippet("find_centroid")]
+fn centroid_search(
+    n: usize,
+    graph: &[Vec<usize>],
     weight_sum: usize,
-    subtree_weight: &Vec<usize>,
-    v: usize,
-    par: usize,
+    subtree_weight: &[usize],
+    order: &[usize],
+    parent: &[usize],
+    removed: &[bool],
 ) -> usize {
-    let mut is_centroid = true;
-    for &next_v in &graph[v] {
-        if next_v == par {
-            continue;
-        }
-        let res = centroid_dfs(n, graph, weight_sum, subtree_weight, next_v, v);
-        if res != n {
-            return res;
+    for &v in order {
+        let mut is_centroid = weight_sum - subtree_weight[v] <= weight_sum / 2;
+        for &next_v in &graph[v] {
+            if next_v == parent[v] || removed[next_v] {
+                continue;
+            }
+            if subtree_weight[next_v] > weight_sum / 2 {
+                is_centroid = false;
+            }
         }
-        if subtree_weight[next_v] > weight_sum / 2 {
-            is_centroid = false;
+        if is_centroid {
+            return v;
         }
     }
-    if weight_sum - subtree_weight[v] > weight_sum / 2 {
-        is_centroid = false;
-    }
-    if is_centroid {
-        v
-    } else {
-        n
-    }
+    n
+}
+
+// start を含む連結成分だけを木とみなして重心を求めます。
+#[snippet("find_centroid")]
+pub fn find_centroid_of_component(
+    graph: &Vec<Vec<usize>>,
+    weight: &Vec<usize>,
+    start: usize,
+) -> usize {
+    let n = graph.len();
+
+    let removed = vec![false; n];
+    let mut order = Vec::new();
+    let mut parent = vec![n; n];
+    post_order(
+        graph,
+        start,
+        &removed,
+        &mut order,
+        &mut parent,
+        &mut Vec::new(),
+    );
+    let weight_sum = order.iter().map(|&v| weight[v]).sum::<usize>();
+    let mut subtree_weight = vec![0; n];
+    subtree_weights(n, weight, &mut subtree_weight, &order, &parent);
+    centroid_search(
+        n,
+        graph,
+        weight_sum,
+        &subtree_weight,
+        &order,
+        &parent,
+        &removed,
+    )
 }
 
 #[snippet("find_centroid")]
 pub fn find_centroid(graph: &Vec<Vec<usize>>, weight: &Vec<usize>) -> usize {
+    find_centroid_of_component(graph, weight, 0)
+}
+
+#[snippet(include = "find_centroid")]
+pub fn find_centroids(graph: &Vec<Vec<usize>>, weight: &Vec<usize>) -> Vec<usize> {
     let n = graph.len();
-    let weight_sum = weight.iter().sum::<usize>();
 
+    let centroid = find_centroid(graph, weight);
+
+    // 重心を根として部分木の重みを求め直すと、隣接頂点側の成分の重みがそのまま部分木の重みになります。
+    let mut order = Vec::new();
+    let mut parent = vec![n; n];
+    post_order(
+        graph,
+        centroid,
+        &vec![false; n],
+        &mut order,
+        &mut parent,
+        &mut Vec::new(),
+    );
+    let weight_sum = order.iter().map(|&v| weight[v]).sum::<usize>();
     let mut subtree_weight = vec![0; n];
-    subtree_dfs(graph, weight, &mut subtree_weight, 0, n);
-    centroid_dfs(n, graph, weight_sum, &subtree_weight, 0, n)
+    subtree_weights(n, weight, &mut subtree_weight, &order, &parent);
+
+    let mut centroids = vec![centroid];
+    for &next_v in &graph[centroid] {
+        if weight_sum - subtree_weight[next_v] <= weight_sum / 2 {
+            centroids.push(next_v);
+        }
+    }
+    centroids
+}
+
+// 重心分解
+// 各頂点の重心木での親（根は None）と、分解の深さを返します。
+#[snippet(include = "find_centroid")]
+pub fn centroid_decomposition(graph: &Vec<Vec<usize>>) -> (Vec<Option<usize>>, Vec<usize>) {
+    let n = graph.len();
+    let weight = vec![1; n];
+    let mut removed = vec![false; n];
+    let mut subtree_weight = vec![0; n];
+    let mut order = Vec::with_capacity(n);
+    let mut parent = vec![n; n];
+    let mut dfs_stack = Vec::with_capacity(n);
+
+    let mut centroid_parent = vec![None; n];
+    let mut level = vec![0; n];
+    let mut stack = Vec::new();
+    if n > 0 {
+        stack.push((0, None, 0));
+    }
+
+    while let Some((root, par, depth)) = stack.pop() {
+        post_order(
+            graph,
+            root,
+            &removed,
+            &mut order,
+            &mut parent,
+            &mut dfs_stack,
+        );
+        subtree_weights(n, &weight, &mut subtree_weight, &order, &parent);
+        let weight_sum = order.len();
+        let centroid = centroid_search(
+            n,
+            graph,
+            weight_sum,
+            &subtree_weight,
+            &order,
+            &parent,
+            &removed,
+        );
+
+        centroid_parent[centroid] = par;
+        level[centroid] = depth;
+        removed[centroid] = true;
+
+        for &next_v in &graph[centroid] {
+            if !removed[next_v] {
+                stack.push((next_v, Some(centroid), depth + 1));
+            }
+        }
+    }
+
+    (centroid_parent, level)
 }
 
 #[cfg(test)]
@@ -167,4 +306,169 @@ mod tests {
             assert_eq!(centroid, 2);
         }
     }
+
+    #[test]
+    fn test_find_centroids() {
+        // Two nodes: both are centroids
+        {
+            let graph = vec![vec![1], vec![0]];
+            let weight = vec![1, 1];
+            let centroids = find_centroids(&graph, &weight);
+            assert_eq!(centroids, vec![1, 0]);
+        }
+
+        // Linear tree with an even number of nodes: 0 - 1 - 2 - 3
+        {
+            let graph = vec![vec![1], vec![0, 2], vec![1, 3], vec![2]];
+            let weight = vec![1; 4];
+            let mut centroids = find_centroids(&graph, &weight);
+            centroids.sort();
+            assert_eq!(centroids, vec![1, 2]);
+        }
+
+        // Balanced binary tree: only the root
+        {
+            let graph = vec![
+                vec![1, 2],
+                vec![0, 3, 4],
+                vec![0, 5, 6],
+                vec![1],
+                vec![1],
+                vec![2],
+                vec![2],
+            ];
+            let weight = vec![1; 7];
+            let centroids = find_centroids(&graph, &weight);
+            assert_eq!(centroids, vec![0]);
+        }
+
+        // The first centroid always matches find_centroid
+        {
+            let graph = vec![vec![1, 2], vec![0], vec![0]];
+            let weight = vec![1, 1, 2];
+            let centroids = find_centroids(&graph, &weight);
+            assert_eq!(centroids[0], find_centroid(&graph, &weight));
+            assert_eq!(centroids, vec![2, 0]);
+        }
+
+        // Forest: only the component of node 0 is considered
+        {
+            let graph = vec![vec![1], vec![0], vec![3], vec![2, 4], vec![3]];
+            let weight = vec![1; 5];
+            let centroids = find_centroids(&graph, &weight);
+            assert_eq!(centroids, vec![1, 0]);
+        }
+    }
+
+    #[test]
+    fn test_find_centroid_long_path() {
+        // 0 - 1 - ... - 99999
+        let n = 100_000;
+        let mut graph = vec![vec![]; n];
+        for i in 0..n - 1 {
+            graph[i].push(i + 1);
+            graph[i + 1].push(i);
+        }
+        let weight = vec![1; n];
+        let centroid = find_centroid(&graph, &weight);
+        // Both 49999 and 50000 are centroids; the deeper one is found first
+        assert_eq!(centroid, 50_000);
+    }
+
+    #[test]
+    fn test_centroid_decomposition() {
+        // Balanced binary tree:
+        //        0
+        //      /   \
+        //     1     2
+        //    / \   / \
+        //   3  4  5  6
+        let graph = vec![
+            vec![1, 2],
+            vec![0, 3, 4],
+            vec![0, 5, 6],
+            vec![1],
+            vec![1],
+            vec![2],
+            vec![2],
+        ];
+        let (parent, level) = centroid_decomposition(&graph);
+        assert_eq!(
+            parent,
+            vec![None, Some(0), Some(0), Some(1), Some(1), Some(2), Some(2)]
+        );
+        assert_eq!(level, vec![0, 1, 1, 2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn test_centroid_decomposition_path() {
+        // 0 - 1 - ... - 14
+        let n = 15;
+        let mut graph = vec![vec![]; n];
+        for i in 0..n - 1 {
+            graph[i].push(i + 1);
+            graph[i + 1].push(i);
+        }
+        let (parent, level) = centroid_decomposition(&graph);
+
+        assert_eq!(parent.iter().filter(|p| p.is_none()).count(), 1);
+        assert_eq!(parent[7], None);
+        // Each level at least halves the component size
+        assert_eq!(*level.iter().max().unwrap(), 3);
+        for v in 0..n {
+            if let Some(p) = parent[v] {
+                assert_eq!(level[p] + 1, level[v]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_centroid_of_component() {
+        // Forest:
+        //   0 - 1 - 2      3 - 4 - 5 - 6 - 7
+        let graph = vec![
+            vec![1],
+            vec![0, 2],
+            vec![1],
+            vec![4],
+            vec![3, 5],
+            vec![4, 6],
+            vec![5, 7],
+            vec![6],
+        ];
+        let weight = vec![1; 8];
+
+        assert_eq!(find_centroid_of_component(&graph, &weight, 0), 1);
+        assert_eq!(find_centroid_of_component(&graph, &weight, 2), 1);
+        assert_eq!(find_centroid_of_component(&graph, &weight, 3), 5);
+        assert_eq!(find_centroid_of_component(&graph, &weight, 7), 5);
+
+        // Weights of the other component are ignored
+        let weight = vec![1, 1, 1, 100, 1, 1, 1, 1];
+        assert_eq!(find_centroid_of_component(&graph, &weight, 0), 1);
+        assert_eq!(find_centroid_of_component(&graph, &weight, 5), 3);
+    }
+
+    #[test]
+    fn test_centroid_decomposition_large() {
+        // Path and star with 200000 nodes each should finish quickly
+        let n = 200_000;
+        let mut path = vec![vec![]; n];
+        let mut star = vec![vec![]; n];
+        for i in 0..n - 1 {
+            path[i].push(i + 1);
+            path[i + 1].push(i);
+            star[0].push(i + 1);
+            star[i + 1].push(0);
+        }
+
+        let (parent, level) = centroid_decomposition(&path);
+        assert_eq!(parent.iter().filter(|p| p.is_none()).count(), 1);
+        assert!(*level.iter().max().unwrap() <= 18);
+
+        let (parent, level) = centroid_decomposition(&star);
+        assert_eq!(parent[0], None);
+        assert!(parent[1..].iter().all(|&p| p == Some(0)));
+        assert!(level[1..].iter().all(|&l| l == 1));
+    }
 }
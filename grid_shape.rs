@@ -1,30 +1,60 @@
 use cargo_snippet::snippet;
 
 #[snippet]
-pub fn rotate_grid(field: &Vec<Vec<char>>) -> Vec<Vec<char>> {
+pub fn rotate_grid<T: Clone>(field: &Vec<Vec<T>>) -> Vec<Vec<T>> {
     let n = field.len();
     let m = field[0].len();
-    let mut result = vec![vec!['.'; n]; m];
 
-    for (i, row) in field.iter().enumerate() {
-        for (j, &c) in row.iter().enumerate() {
-            result[j][n - 1 - i] = c;
+    // result[j][n - 1 - i] = field[i][j]
+    (0..m)
+        .map(|j| (0..n).rev().map(|i| field[i][j].clone()).collect())
+        .collect()
+}
+
+// 時計回りに 90° * k 回転します。k % 4 == 0 のときはそのままのコピーを返します。
+#[snippet(include = "rotate_grid")]
+pub fn rotate_grid_times<T: Clone>(field: &Vec<Vec<T>>, k: usize) -> Vec<Vec<T>> {
+    match k % 4 {
+        0 => field.clone(),
+        1 => rotate_grid(field),
+        2 => field
+            .iter()
+            .rev()
+            .map(|row| row.iter().rev().cloned().collect())
+            .collect(),
+        _ => {
+            let m = field[0].len();
+            // result[m - 1 - j][i] = field[i][j]
+            (0..m)
+                .rev()
+                .map(|j| field.iter().map(|row| row[j].clone()).collect())
+                .collect()
         }
     }
+}
+
+#[snippet]
+pub fn transpose<T: Clone>(field: &Vec<Vec<T>>) -> Vec<Vec<T>> {
+    if field.is_empty() {
+        return Vec::new();
+    }
 
-    result
+    let m = field[0].len();
+    (0..m)
+        .map(|j| field.iter().map(|row| row[j].clone()).collect())
+        .collect()
 }
 
 #[snippet]
-pub fn trim_grid(field: &Vec<Vec<char>>) -> Vec<Vec<char>> {
+pub fn trim_grid_by<T: Clone, F: Fn(&T) -> bool>(field: &Vec<Vec<T>>, is_ink: F) -> Vec<Vec<T>> {
     let mut top = field.len();
     let mut bottom = 0;
     let mut left = field[0].len();
     let mut right = 0;
 
     for (i, row) in field.iter().enumerate() {
-        for (j, &c) in row.iter().enumerate() {
-            if c == '#' {
+        for (j, c) in row.iter().enumerate() {
+            if is_ink(c) {
                 top = top.min(i);
                 bottom = bottom.max(i);
                 left = left.min(j);
@@ -43,6 +73,82 @@ pub fn trim_grid(field: &Vec<Vec<char>>) -> Vec<Vec<char>> {
         .collect()
 }
 
+#[snippet(include = "trim_grid_by")]
+pub fn trim_grid(field: &Vec<Vec<char>>) -> Vec<Vec<char>> {
+    trim_grid_by(field, |c| *c == '#')
+}
+
+#[snippet]
+pub fn pad_grid<T: Clone>(field: &Vec<Vec<T>>, thickness: usize, fill: T) -> Vec<Vec<T>> {
+    let h = field.len();
+    let w = if h == 0 { 0 } else { field[0].len() };
+    let mut result = vec![vec![fill; w + 2 * thickness]; h + 2 * thickness];
+
+    for (i, row) in field.iter().enumerate() {
+        result[i + thickness][thickness..thickness + w].clone_from_slice(row);
+    }
+
+    result
+}
+
+#[snippet]
+pub fn overlay_grid<T: Clone + PartialEq>(
+    base: &mut Vec<Vec<T>>,
+    patch: &Vec<Vec<T>>,
+    top: usize,
+    left: usize,
+    transparent: Option<T>,
+) {
+    for (i, row) in patch.iter().enumerate() {
+        if top + i >= base.len() {
+            break;
+        }
+        let base_row = &mut base[top + i];
+        for (j, c) in row.iter().enumerate() {
+            if left + j >= base_row.len() {
+                break;
+            }
+            if transparent.as_ref() == Some(c) {
+                continue;
+            }
+            base_row[left + j] = c.clone();
+        }
+    }
+}
+
+#[snippet]
+pub fn flip_grid<T: Clone>(field: &Vec<Vec<T>>) -> Vec<Vec<T>> {
+    field
+        .iter()
+        .map(|row| row.iter().rev().cloned().collect())
+        .collect()
+}
+
+// 回転・反転の 8 通りのうち、'#' の外接矩形で切り出したものが辞書順最小になるものを返します。
+#[snippet(include = "rotate_grid, flip_grid, trim_grid")]
+pub fn canonical_grid(field: &Vec<Vec<char>>) -> Vec<Vec<char>> {
+    if field.is_empty() {
+        return Vec::new();
+    }
+
+    // 切り出しは回転・反転と可換なので先に一度だけ行います。
+    let trimmed = trim_grid(field);
+    if trimmed.is_empty() {
+        return trimmed;
+    }
+
+    let mut result = trimmed.clone();
+    for mut shape in [trimmed.clone(), flip_grid(&trimmed)] {
+        for _ in 0..4 {
+            shape = rotate_grid(&shape);
+            if shape < result {
+                result = shape.clone();
+            }
+        }
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,6 +209,61 @@ mod tests {
         assert_eq!(field, full_circle_rotated);
     }
 
+    #[test]
+    fn test_rotate_grid_u8() {
+        let field: Vec<Vec<u8>> = vec![vec![1, 2, 3], vec![4, 5, 6]];
+
+        let rotated = rotate_grid(&field);
+
+        let expected: Vec<Vec<u8>> = vec![vec![4, 1], vec![5, 2], vec![6, 3]];
+
+        assert_eq!(rotated, expected);
+
+        let full_circle_rotated = rotate_grid(&rotate_grid(&rotate_grid(&rotated)));
+        assert_eq!(field, full_circle_rotated);
+    }
+
+    #[test]
+    fn test_rotate_grid_times() {
+        let field = vec![
+            vec!['.', '.', '#'],
+            vec!['.', '#', '.'],
+            vec!['#', '.', '.'],
+            vec!['#', '.', '#'],
+        ];
+
+        assert_eq!(rotate_grid_times(&field, 0), field);
+        assert_eq!(rotate_grid_times(&field, 1), rotate_grid(&field));
+        assert_eq!(
+            rotate_grid_times(&field, 2),
+            rotate_grid(&rotate_grid(&field))
+        );
+        assert_eq!(
+            rotate_grid_times(&field, 3),
+            rotate_grid(&rotate_grid(&rotate_grid(&field)))
+        );
+        assert_eq!(rotate_grid_times(&field, 4), field);
+        assert_eq!(rotate_grid_times(&field, 7), rotate_grid_times(&field, 3));
+    }
+
+    #[test]
+    fn test_transpose() {
+        let field = vec![vec![1, 2, 3], vec![4, 5, 6]];
+
+        let transposed = transpose(&field);
+
+        let expected = vec![vec![1, 4], vec![2, 5], vec![3, 6]];
+
+        assert_eq!(transposed, expected);
+        assert_eq!(transpose(&transposed), field);
+    }
+
+    #[test]
+    fn test_transpose_empty_field() {
+        let field: Vec<Vec<char>> = Vec::new();
+        assert!(transpose(&field).is_empty());
+    }
+
     #[test]
     fn test_trim_grid() {
         let field = vec![
@@ -171,4 +332,158 @@ mod tests {
 
         assert_eq!(trimmed, expected);
     }
+
+    #[test]
+    fn test_trim_grid_by() {
+        let field = vec![
+            vec!['.', '.', '.', '.', '.'],
+            vec!['.', 'o', '.', '.', '.'],
+            vec!['.', '.', '.', 'x', '.'],
+            vec!['.', '.', '#', '.', '.'],
+            vec!['.', '.', '.', '.', '.'],
+        ];
+
+        let trimmed = trim_grid_by(&field, |&c| c == 'o' || c == 'x');
+
+        let expected = vec![vec!['o', '.', '.'], vec!['.', '.', 'x']];
+
+        assert_eq!(trimmed, expected);
+
+        let trimmed = trim_grid_by(&field, |&c| c != '.');
+
+        let expected = vec![
+            vec!['o', '.', '.'],
+            vec!['.', '.', 'x'],
+            vec!['.', '#', '.'],
+        ];
+
+        assert_eq!(trimmed, expected);
+    }
+
+    #[test]
+    fn test_trim_grid_by_no_match() {
+        let field = vec![vec![0, 0, 0], vec![0, 0, 0]];
+
+        let trimmed = trim_grid_by(&field, |&x| x > 0);
+
+        assert!(trimmed.is_empty());
+    }
+
+    #[test]
+    fn test_pad_grid() {
+        let field = vec![vec!['.', '#'], vec!['#', '.'], vec!['.', '.']];
+
+        let padded = pad_grid(&field, 1, '#');
+
+        let expected = vec![
+            vec!['#', '#', '#', '#'],
+            vec!['#', '.', '#', '#'],
+            vec!['#', '#', '.', '#'],
+            vec!['#', '.', '.', '#'],
+            vec!['#', '#', '#', '#'],
+        ];
+
+        assert_eq!(padded, expected);
+
+        let padded = pad_grid(&field, 3, '#');
+        assert_eq!(padded.len(), 3 + 2 * 3);
+        assert!(padded.iter().all(|row| row.len() == 2 + 2 * 3));
+        for (i, row) in field.iter().enumerate() {
+            assert_eq!(&padded[i + 3][3..5], &row[..]);
+        }
+
+        assert_eq!(pad_grid(&field, 0, '#'), field);
+    }
+
+    #[test]
+    fn test_pad_grid_empty_field() {
+        let field: Vec<Vec<u8>> = Vec::new();
+
+        assert_eq!(pad_grid(&field, 2, 0), vec![vec![0; 4]; 4]);
+        assert!(pad_grid(&field, 0, 0).is_empty());
+    }
+
+    #[test]
+    fn test_overlay_grid_clipped() {
+        let mut base = vec![vec!['.'; 3]; 3];
+        let patch = vec![vec!['a', 'b'], vec!['c', 'd']];
+
+        overlay_grid(&mut base, &patch, 2, 2, None);
+
+        let expected = vec![
+            vec!['.', '.', '.'],
+            vec!['.', '.', '.'],
+            vec!['.', '.', 'a'],
+        ];
+
+        assert_eq!(base, expected);
+
+        overlay_grid(&mut base, &patch, 5, 0, None);
+        assert_eq!(base, expected);
+    }
+
+    #[test]
+    fn test_overlay_grid_transparent() {
+        let mut base = vec![vec!['.'; 4]; 3];
+        let patch = vec![vec!['#', '.'], vec!['.', '#']];
+
+        overlay_grid(&mut base, &patch, 0, 1, Some('.'));
+        overlay_grid(&mut base, &patch, 1, 2, Some('.'));
+
+        let expected = vec![
+            vec!['.', '#', '.', '.'],
+            vec!['.', '.', '#', '.'],
+            vec!['.', '.', '.', '#'],
+        ];
+
+        assert_eq!(base, expected);
+    }
+
+    #[test]
+    fn test_flip_grid() {
+        let field = vec![vec![1, 2, 3], vec![4, 5, 6]];
+
+        let flipped = flip_grid(&field);
+
+        let expected = vec![vec![3, 2, 1], vec![6, 5, 4]];
+
+        assert_eq!(flipped, expected);
+        assert_eq!(flip_grid(&flipped), field);
+    }
+
+    #[test]
+    fn test_canonical_grid() {
+        // L-tromino
+        let field = vec![
+            vec!['.', '.', '.', '.'],
+            vec!['.', '#', '.', '.'],
+            vec!['.', '#', '#', '.'],
+            vec!['.', '.', '.', '.'],
+        ];
+
+        let canonical = canonical_grid(&field);
+
+        let expected = vec![vec!['#', '#'], vec!['#', '.']];
+
+        assert_eq!(canonical, expected);
+
+        let mut rotated = field.clone();
+        for _ in 0..4 {
+            rotated = rotate_grid(&rotated);
+            assert_eq!(canonical_grid(&rotated), canonical);
+            assert_eq!(canonical_grid(&flip_grid(&rotated)), canonical);
+        }
+    }
+
+    #[test]
+    fn test_canonical_grid_distinct_shapes() {
+        // S-tetromino and Z-tetromino are mirror images of each other
+        let s = vec![vec!['.', '#', '#'], vec!['#', '#', '.']];
+        let z = vec![vec!['#', '#', '.'], vec!['.', '#', '#']];
+        // T-tetromino
+        let t = vec![vec!['#', '#', '#'], vec!['.', '#', '.']];
+
+        assert_eq!(canonical_grid(&s), canonical_grid(&z));
+        assert_ne!(canonical_grid(&s), canonical_grid(&t));
+    }
 }
@@ -2,9 +2,12 @@ pub mod bfs;
 pub mod combination;
 pub mod dfs;
 pub mod dijkstra;
+pub mod distinct_in_windows;
 pub mod doubling;
+pub mod edit_distance;
 pub mod find_centroid;
 pub mod grid_shape;
+pub mod longest_palindromic_subsequence;
 pub mod modulo;
 pub mod positional_notation;
 pub mod prime;
@@ -0,0 +1,114 @@
+use cargo_snippet::snippet;
+
+// dp[i][j] = s[i..=j] の最長回文部分列の長さ
+//   s[i] == s[j] のとき dp[i][j] = dp[i+1][j-1] + 2
+//   そうでないとき       dp[i][j] = max(dp[i+1][j], dp[i][j-1])
+// O(n^2)
+#[snippet]
+pub fn palindromic_subsequence_table(s: &[char]) -> Vec<Vec<usize>> {
+    let n = s.len();
+    let mut dp = vec![vec![0; n]; n];
+    for i in (0..n).rev() {
+        dp[i][i] = 1;
+        for j in i + 1..n {
+            dp[i][j] = if s[i] == s[j] {
+                dp[i + 1][j - 1] + 2
+            } else {
+                dp[i + 1][j].max(dp[i][j - 1])
+            };
+        }
+    }
+    dp
+}
+
+// 最長回文部分列の長さ（連続でなくてよい）
+#[snippet(include = "palindromic_subsequence_table")]
+pub fn longest_palindromic_subsequence(s: &[char]) -> usize {
+    if s.is_empty() {
+        return 0;
+    }
+    palindromic_subsequence_table(s)[0][s.len() - 1]
+}
+
+// 最長回文部分列を1つ復元して返します。
+#[snippet(include = "palindromic_subsequence_table")]
+pub fn restore_longest_palindromic_subsequence(s: &[char]) -> Vec<char> {
+    let n = s.len();
+    if n == 0 {
+        return vec![];
+    }
+    let dp = palindromic_subsequence_table(s);
+
+    // 両端から dp をたどり、左半分と中央を集めます。
+    let mut left = vec![];
+    let mut middle = None;
+    let (mut i, mut j) = (0, n - 1);
+    while i <= j {
+        if i == j {
+            middle = Some(s[i]);
+            break;
+        }
+        if s[i] == s[j] {
+            left.push(s[i]);
+            if i + 1 == j {
+                break;
+            }
+            i += 1;
+            j -= 1;
+        } else if dp[i + 1][j] >= dp[i][j - 1] {
+            i += 1;
+        } else {
+            j -= 1;
+        }
+    }
+
+    let mut result = left.clone();
+    result.extend(middle);
+    result.extend(left.iter().rev());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_subsequence(t: &[char], s: &[char]) -> bool {
+        let mut it = s.iter();
+        t.iter().all(|c| it.any(|d| d == c))
+    }
+
+    #[test]
+    fn test_longest_palindromic_subsequence() {
+        let s: Vec<char> = "bbbab".chars().collect();
+        assert_eq!(longest_palindromic_subsequence(&s), 4);
+        let s: Vec<char> = "cbbd".chars().collect();
+        assert_eq!(longest_palindromic_subsequence(&s), 2);
+        let s: Vec<char> = "a".chars().collect();
+        assert_eq!(longest_palindromic_subsequence(&s), 1);
+        let s: Vec<char> = "abcde".chars().collect();
+        assert_eq!(longest_palindromic_subsequence(&s), 1);
+        let s: Vec<char> = "character".chars().collect();
+        assert_eq!(longest_palindromic_subsequence(&s), 5);
+        assert_eq!(longest_palindromic_subsequence(&[]), 0);
+    }
+
+    #[test]
+    fn test_restore_longest_palindromic_subsequence() {
+        for s in [
+            "bbbab",
+            "cbbd",
+            "a",
+            "abcde",
+            "character",
+            "agbdba",
+            "abacdfgdcaba",
+            "",
+        ] {
+            let s: Vec<char> = s.chars().collect();
+            let t = restore_longest_palindromic_subsequence(&s);
+            assert_eq!(t.len(), longest_palindromic_subsequence(&s));
+            assert!(t.iter().eq(t.iter().rev()));
+            assert!(is_subsequence(&t, &s));
+        }
+    }
+}
@@ -1,27 +1,178 @@
 use cargo_snippet::snippet;
 
 // 繰り返し二乗法
+// 掛け算は u128 で行うので、modulo が usize::MAX に近くてもオーバーフローしません。
 #[snippet]
 pub fn mod_pow(base: usize, exp: usize, modulo: usize) -> usize {
-    if exp == 0 {
-        return 1;
+    let modulo = modulo as u128;
+    let mut base = base as u128 % modulo;
+    let mut exp = exp;
+    let mut result = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulo;
+        }
+        base = base * base % modulo;
+        exp >>= 1;
     }
-    let base = base % modulo;
-    let mut result = mod_pow(base * base % modulo, exp / 2, modulo);
-    if exp % 2 == 1 {
-        result *= base;
-        result %= modulo;
-    }
-    result
+    result as usize
 }
 
 // 逆元 num^(modulo-2) : mod modulo
 // a*a^(p-2)≡1 : mod p
+// フェルマーの小定理を使うので modulo が素数のときのみ正しいです。素数でない場合は mod_inv_ext を使います。
 #[snippet(include = "mod_pow")]
 pub fn mod_inv(num: usize, modulo: usize) -> usize {
     mod_pow(num, modulo - 2, modulo)
 }
 
+// ユークリッドの互除法
+#[snippet]
+pub fn gcd(a: usize, b: usize) -> usize {
+    let (mut a, mut b) = (a, b);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// 先に gcd で割ってから掛けるので、a * b がオーバーフローしても lcm が収まれば計算できます。
+#[snippet(include = "gcd")]
+pub fn lcm(a: usize, b: usize) -> usize {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    a / gcd(a, b) * b
+}
+
+// 拡張ユークリッドの互除法
+// a * x + b * y = g となる (g, x, y) を返します。a, b ≥ 0 のとき g = gcd(a, b) です。
+#[snippet]
+pub fn ext_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    // old_r = a * old_x + b * old_y, r = a * x + b * y を保ちながら互除法を進めます。
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    (old_r, old_x, old_y)
+}
+
+// 拡張ユークリッドの互除法による逆元 a^(-1) : mod m
+// m が素数でなくても使えます。gcd(a, m) != 1 のときは逆元が存在しないので None を返します。
+#[snippet(include = "ext_gcd")]
+pub fn mod_inv_ext(a: i64, m: i64) -> Option<i64> {
+    // a * x + m * y = 1 なら a * x ≡ 1 (mod m)
+    let (g, x, _) = ext_gcd(a.rem_euclid(m), m);
+    if g != 1 {
+        return None;
+    }
+    Some(x.rem_euclid(m))
+}
+
+// 中国剰余定理
+// x ≡ r[i] (mod m[i]) をすべて満たす x を (x mod lcm, lcm) の形で返します。解がなければ None です。
+// m[i] 同士が互いに素でなくても使えます。lcm が i64 に収まることを仮定しています。
+#[snippet(include = "ext_gcd")]
+pub fn crt(r: &[i64], m: &[i64]) -> Option<(i64, i64)> {
+    assert_eq!(r.len(), m.len());
+    let (mut r0, mut m0) = (0, 1);
+    for (&ri, &mi) in r.iter().zip(m) {
+        let ri = ri.rem_euclid(mi);
+
+        // m0 * x + mi * y = g より (m0/g) * x ≡ 1 (mod mi/g)
+        let (g, x, _) = ext_gcd(m0, mi);
+        if (ri - r0) % g != 0 {
+            return None;
+        }
+
+        // r0 + m0 * t ≡ ri (mod mi) となる t を (m0/g) * t ≡ (ri-r0)/g (mod mi/g) から求めます。
+        let u = mi / g;
+        let inv = x.rem_euclid(u);
+        let t = ((ri - r0) / g).rem_euclid(u) as i128 * inv as i128 % u as i128;
+        r0 += m0 * t as i64;
+        m0 *= u;
+        r0 = r0.rem_euclid(m0);
+    }
+    Some((r0, m0))
+}
+
+// 複数の値の逆元をまとめて求めます。mod_pow は1回だけで、残りは O(n) 回の掛け算です。
+// modulo は素数、values はすべて modulo の倍数でないことを仮定しています。
+// prefix[i] = values[0] * ... * values[i-1] とすると values[i]^(-1) = prefix[i] * (prefix[i+1])^(-1)
+#[snippet(include = "mod_inv")]
+pub fn mod_inv_batch(values: &[usize], modulo: usize) -> Vec<usize> {
+    let n = values.len();
+    let mut prefix = vec![1 % modulo; n + 1];
+    for (i, &v) in values.iter().enumerate() {
+        prefix[i + 1] = (prefix[i] as u128 * (v % modulo) as u128 % modulo as u128) as usize;
+    }
+
+    // inv_all は prefix[i+1] の逆元で、後ろから values[i] を掛けて prefix[i] の逆元にしていきます。
+    let mut inv_all = mod_inv(prefix[n], modulo);
+    let mut result = vec![0; n];
+    for i in (0..n).rev() {
+        result[i] = (inv_all as u128 * prefix[i] as u128 % modulo as u128) as usize;
+        inv_all = (inv_all as u128 * (values[i] % modulo) as u128 % modulo as u128) as usize;
+    }
+    result
+}
+
+// Tonelli–Shanks のアルゴリズムで、素数 p を法とする a の平方根の1つを返します。
+// a が平方非剰余なら None です。もう1つの平方根は p - r です。
+#[snippet(include = "mod_pow")]
+pub fn mod_sqrt(a: usize, p: usize) -> Option<usize> {
+    let a = a % p;
+    if a == 0 || p == 2 {
+        return Some(a);
+    }
+    // オイラーの規準: a^((p-1)/2) ≡ 1 なら平方剰余
+    if mod_pow(a, (p - 1) / 2, p) != 1 {
+        return None;
+    }
+
+    let mul = |x: usize, y: usize| (x as u128 * y as u128 % p as u128) as usize;
+
+    // p - 1 = q * 2^s (q は奇数)
+    let s = (p - 1).trailing_zeros();
+    let q = (p - 1) >> s;
+
+    // 平方非剰余 z を1つ見つけます。
+    let mut z = 2;
+    while mod_pow(z, (p - 1) / 2, p) != p - 1 {
+        z += 1;
+    }
+
+    // r^2 ≡ a * t を保ちながら、t の位数を下げて t = 1 にします。
+    let mut m = s;
+    let mut c = mod_pow(z, q, p);
+    let mut t = mod_pow(a, q, p);
+    let mut r = mod_pow(a, q.div_ceil(2), p);
+    while t != 1 {
+        // t^(2^i) = 1 となる最小の i
+        let mut i = 0;
+        let mut t2 = t;
+        while t2 != 1 {
+            t2 = mul(t2, t2);
+            i += 1;
+        }
+
+        let mut b = c;
+        for _ in 0..m - i - 1 {
+            b = mul(b, b);
+        }
+        m = i;
+        c = mul(b, b);
+        t = mul(t, c);
+        r = mul(r, b);
+    }
+    Some(r)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,4 +191,146 @@ mod tests {
         assert_eq!(mod_inv(7, 13), 2);
         assert_eq!(mod_inv(2, MOD), 499122177);
     }
+
+    #[test]
+    fn test_mod_pow_large_modulo() {
+        // 2^61 - 1 is prime, so a^(p-1) ≡ 1 by Fermat's little theorem
+        let p: usize = (1 << 61) - 1;
+        assert_eq!(mod_pow(3, p - 1, p), 1);
+        assert_eq!(mod_pow(p - 1, 2, p), 1);
+        assert_eq!(mod_pow(2, 61, p), 1);
+        assert_eq!(mod_inv(1 << 40, p) as u128 * (1 << 40) % p as u128, 1);
+
+        // modulus around 2^62: (m-1)^2 would overflow a u64 multiply
+        let m: usize = (1 << 62) + 1;
+        assert_eq!(mod_pow(m - 1, 2, m), 1);
+        assert_eq!(mod_pow(m - 1, 3, m), m - 1);
+        assert_eq!(mod_pow(1 << 31, 2, m), m - 1);
+    }
+
+    #[test]
+    fn test_mod_inv_ext() {
+        assert_eq!(mod_inv_ext(3, 10), Some(7));
+        assert_eq!(mod_inv_ext(2, 4), None);
+        assert_eq!(mod_inv_ext(6, 9), None);
+        assert_eq!(mod_inv_ext(-3, 10), Some(3));
+        assert_eq!(mod_inv_ext(13, 10), Some(7));
+        assert_eq!(mod_inv_ext(5, 1), Some(0));
+
+        let m = 1_000_000;
+        for a in [1, 3, 7, 999_999, 123_457] {
+            let inv = mod_inv_ext(a, m).unwrap();
+            assert!((0..m).contains(&inv));
+            assert_eq!(a * inv % m, 1);
+        }
+
+        // agrees with the Fermat version for prime moduli
+        for a in 1..13 {
+            assert_eq!(mod_inv_ext(a, 13), Some(mod_inv(a as usize, 13) as i64));
+        }
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[2, 3], &[3, 5]), Some((8, 15)));
+        assert_eq!(crt(&[2, 3, 2], &[3, 5, 7]), Some((23, 105)));
+        assert_eq!(crt(&[], &[]), Some((0, 1)));
+        assert_eq!(crt(&[-1], &[7]), Some((6, 7)));
+
+        // non-coprime moduli
+        assert_eq!(crt(&[1, 3], &[4, 6]), Some((9, 12)));
+        assert_eq!(crt(&[3, 3], &[6, 6]), Some((3, 6)));
+        assert_eq!(crt(&[1, 2], &[4, 6]), None);
+        assert_eq!(crt(&[0, 1], &[2, 4]), None);
+
+        // large coprime moduli whose product still fits in i64
+        let m = [1_000_000_007, 998_244_353];
+        let x: i64 = 123_456_789_012_345_678;
+        let (v, l) = crt(&[x % m[0], x % m[1]], &m).unwrap();
+        assert_eq!(l, m[0] * m[1]);
+        assert_eq!(v, x % l);
+    }
+
+    #[test]
+    fn test_mod_inv_batch() {
+        let values = vec![1, 2, 3, 4, 5, 123_456_789, MOD - 1, MOD + 7];
+        let inv = mod_inv_batch(&values, MOD);
+        assert_eq!(inv.len(), values.len());
+        for (&v, &iv) in values.iter().zip(&inv) {
+            assert_eq!(v % MOD * iv % MOD, 1);
+            assert_eq!(iv, mod_inv(v % MOD, MOD));
+        }
+
+        assert_eq!(mod_inv_batch(&[3, 5, 6], 7), vec![5, 3, 6]);
+        assert_eq!(mod_inv_batch(&[], MOD), vec![]);
+    }
+
+    #[test]
+    fn test_mod_sqrt() {
+        let r = mod_sqrt(2, 7).unwrap();
+        assert!(r == 3 || r == 4);
+        assert_eq!(mod_sqrt(3, 7), None);
+        assert_eq!(mod_sqrt(0, 7), Some(0));
+        assert_eq!(mod_sqrt(14, 7), Some(0));
+        assert_eq!(mod_sqrt(1, 2), Some(1));
+
+        // 998244353 - 1 = 119 * 2^23 exercises the Tonelli–Shanks loop
+        for p in [7, 13, 17, 41, 97, MOD, 1_000_000_007] {
+            let mut residues = 0;
+            for a in 0..300 {
+                match mod_sqrt(a, p) {
+                    Some(r) => {
+                        assert_eq!(r * r % p, a % p);
+                        residues += 1;
+                    }
+                    None => assert_eq!(mod_pow(a % p, (p - 1) / 2, p), p - 1),
+                }
+            }
+            assert!(residues > 0);
+        }
+
+        // exactly half of the nonzero residues mod 17 are quadratic residues
+        let count = (1..17).filter(|&a| mod_sqrt(a, 17).is_some()).count();
+        assert_eq!(count, 8);
+    }
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(18, 12), 6);
+        assert_eq!(gcd(7, 13), 1);
+        assert_eq!(gcd(0, 5), 5);
+        assert_eq!(gcd(5, 0), 5);
+        assert_eq!(gcd(0, 0), 0);
+
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(7, 13), 91);
+        assert_eq!(lcm(0, 5), 0);
+        // a * b = 2^80 * 15 overflows, but the lcm 2^40 * 15 fits
+        let a = 3 << 40;
+        let b = 5 << 40;
+        assert_eq!(lcm(a, b), 15 << 40);
+        assert_eq!(
+            lcm(1_000_000_007 * 4, 1_000_000_007 * 6),
+            1_000_000_007 * 12
+        );
+    }
+
+    #[test]
+    fn test_ext_gcd() {
+        for (a, b) in [
+            (3, 10),
+            (240, 46),
+            (46, 240),
+            (0, 7),
+            (7, 0),
+            (1, 1),
+            (998_244_353, 1_000_000_007),
+        ] {
+            let (g, x, y) = ext_gcd(a, b);
+            assert_eq!(g, gcd(a as usize, b as usize) as i64);
+            assert_eq!(a * x + b * y, g);
+        }
+        assert_eq!(ext_gcd(240, 46).0, 2);
+    }
 }
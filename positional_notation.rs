@@ -1,18 +1,22 @@
 use cargo_snippet::snippet;
 
+// '0'..='9' と 'a'..='z'（大文字も可）を 0..=35 の数字として読みます。
+// base 以上の数字や数字でない文字が含まれる場合は panic します。
 #[snippet]
 pub fn chars_to_decimal(n: Vec<char>, base: usize) -> usize {
     let mut result = 0;
-    let mut x = 1;
-    for &c in n.iter().rev() {
-        result += (c as usize - '0' as usize) * x;
-        x *= base;
+    for &c in &n {
+        let digit = c
+            .to_digit(base as u32)
+            .unwrap_or_else(|| panic!("invalid digit {:?} for base {}", c, base));
+        result = result * base + digit as usize;
     }
     result
 }
 
 #[snippet]
 pub fn decimal_to_chars(mut n: usize, base: usize) -> Vec<char> {
+    assert!((2..=36).contains(&base), "base must be in 2..=36");
     if n == 0 {
         return vec!['0'];
     }
@@ -25,6 +29,89 @@ pub fn decimal_to_chars(mut n: usize, base: usize) -> Vec<char> {
     result.iter().rev().copied().collect()
 }
 
+// from 進数の数字列を to 進数の数字列に直接変換します。usize を経由しないので桁数に制限はありません。
+// from 進数のまま to で割り続け、余りを下の桁から並べます。O(桁数^2)
+#[snippet]
+pub fn convert_base(digits: &[char], from: usize, to: usize) -> Vec<char> {
+    assert!((2..=36).contains(&from), "base must be in 2..=36");
+    assert!((2..=36).contains(&to), "base must be in 2..=36");
+    let mut num: Vec<usize> = digits
+        .iter()
+        .map(|&c| {
+            c.to_digit(from as u32)
+                .unwrap_or_else(|| panic!("invalid digit {:?} for base {}", c, from))
+                as usize
+        })
+        .skip_while(|&d| d == 0)
+        .collect();
+
+    if num.is_empty() {
+        return vec!['0'];
+    }
+
+    let mut result = Vec::new();
+    while !num.is_empty() {
+        // num を to で割った商を num に、余りを rem に入れます。
+        let mut rem = 0;
+        for d in num.iter_mut() {
+            let cur = rem * from + *d;
+            *d = cur / to;
+            rem = cur % to;
+        }
+        result.push(std::char::from_digit(rem as u32, to as u32).unwrap());
+
+        let leading_zeros = num.iter().take_while(|&&d| d == 0).count();
+        num.drain(..leading_zeros);
+    }
+    result.reverse();
+    result
+}
+
+#[snippet("ConversionError")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionError {
+    // base が 2..=36 の範囲外
+    InvalidBase(usize),
+    // base 進数の数字として読めない文字
+    InvalidDigit(char),
+    // 空の数字列
+    Empty,
+    // 結果が usize に収まらない
+    Overflow,
+}
+
+// chars_to_decimal の panic しない版です。
+#[snippet(include = "ConversionError")]
+pub fn try_chars_to_decimal(chars: &[char], base: usize) -> Result<usize, ConversionError> {
+    if !(2..=36).contains(&base) {
+        return Err(ConversionError::InvalidBase(base));
+    }
+    if chars.is_empty() {
+        return Err(ConversionError::Empty);
+    }
+
+    let mut result: usize = 0;
+    for &c in chars {
+        let digit = c
+            .to_digit(base as u32)
+            .ok_or(ConversionError::InvalidDigit(c))?;
+        result = result
+            .checked_mul(base)
+            .and_then(|r| r.checked_add(digit as usize))
+            .ok_or(ConversionError::Overflow)?;
+    }
+    Ok(result)
+}
+
+// decimal_to_chars の panic しない版です。
+#[snippet(include = "ConversionError, decimal_to_chars")]
+pub fn try_decimal_to_chars(n: usize, base: usize) -> Result<Vec<char>, ConversionError> {
+    if !(2..=36).contains(&base) {
+        return Err(ConversionError::InvalidBase(base));
+    }
+    Ok(decimal_to_chars(n, base))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -38,4 +125,123 @@ mod tests {
     fn test_decimal_to_chars() {
         assert_eq!(decimal_to_chars(17, 9), vec!['1', '8'])
     }
+
+    #[test]
+    fn test_chars_to_decimal_letters() {
+        assert_eq!(chars_to_decimal(vec!['1', 'a'], 16), 26);
+        assert_eq!(chars_to_decimal(vec!['F', 'f'], 16), 255);
+        assert_eq!(chars_to_decimal(vec!['z'], 36), 35);
+        assert_eq!(chars_to_decimal(vec!['0'], 2), 0);
+
+        for base in 2..=36 {
+            for n in [0, 1, 35, 36, 1295, 123_456_789] {
+                assert_eq!(chars_to_decimal(decimal_to_chars(n, base), base), n);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_chars_to_decimal_digit_out_of_range() {
+        chars_to_decimal(vec!['1', 'z'], 10);
+    }
+
+    #[test]
+    fn test_convert_base() {
+        let binary: Vec<char> = "11111111".chars().collect();
+        let hex = convert_base(&binary, 2, 16);
+        assert_eq!(hex, vec!['f', 'f']);
+        assert_eq!(convert_base(&hex, 16, 2), binary);
+
+        assert_eq!(convert_base(&['0', '0'], 10, 2), vec!['0']);
+        assert_eq!(convert_base(&['0', '1', '7'], 10, 9), vec!['1', '8']);
+        for n in [0, 1, 17, 255, 123_456_789] {
+            for (from, to) in [(10, 2), (2, 36), (36, 7), (16, 10)] {
+                let digits = decimal_to_chars(n, from);
+                assert_eq!(convert_base(&digits, from, to), decimal_to_chars(n, to));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_convert_base_invalid_to() {
+        convert_base(&['5'], 10, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_convert_base_invalid_from() {
+        convert_base(&['5'], 37, 10);
+    }
+
+    #[test]
+    fn test_convert_base_beyond_u64() {
+        let decimal: Vec<char> = u128::MAX.to_string().chars().collect();
+        let hex = convert_base(&decimal, 10, 16);
+        assert_eq!(hex, vec!['f'; 32]);
+        assert_eq!(convert_base(&hex, 16, 10), decimal);
+
+        // 10^40 has more digits than any u128
+        let mut big = vec!['1'];
+        big.extend(vec!['0'; 40]);
+        let base3 = convert_base(&big, 10, 3);
+        assert_eq!(convert_base(&base3, 3, 10), big);
+    }
+
+    #[test]
+    fn test_try_chars_to_decimal() {
+        assert_eq!(try_chars_to_decimal(&['2', '1'], 8), Ok(17));
+        assert_eq!(try_chars_to_decimal(&['1', 'A'], 16), Ok(26));
+        assert_eq!(try_chars_to_decimal(&['0'], 2), Ok(0));
+
+        assert_eq!(
+            try_chars_to_decimal(&['1', 'z'], 10),
+            Err(ConversionError::InvalidDigit('z'))
+        );
+        assert_eq!(
+            try_chars_to_decimal(&['2'], 2),
+            Err(ConversionError::InvalidDigit('2'))
+        );
+        assert_eq!(
+            try_chars_to_decimal(&['-', '1'], 10),
+            Err(ConversionError::InvalidDigit('-'))
+        );
+        assert_eq!(try_chars_to_decimal(&[], 10), Err(ConversionError::Empty));
+        assert_eq!(
+            try_chars_to_decimal(&['1'], 1),
+            Err(ConversionError::InvalidBase(1))
+        );
+        assert_eq!(
+            try_chars_to_decimal(&['1'], 37),
+            Err(ConversionError::InvalidBase(37))
+        );
+
+        let max: Vec<char> = usize::MAX.to_string().chars().collect();
+        assert_eq!(try_chars_to_decimal(&max, 10), Ok(usize::MAX));
+        let mut over = max.clone();
+        over.push('0');
+        assert_eq!(
+            try_chars_to_decimal(&over, 10),
+            Err(ConversionError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_try_decimal_to_chars() {
+        assert_eq!(try_decimal_to_chars(17, 9), Ok(vec!['1', '8']));
+        assert_eq!(try_decimal_to_chars(35, 36), Ok(vec!['z']));
+        assert_eq!(
+            try_decimal_to_chars(17, 0),
+            Err(ConversionError::InvalidBase(0))
+        );
+        assert_eq!(
+            try_decimal_to_chars(17, 1),
+            Err(ConversionError::InvalidBase(1))
+        );
+        assert_eq!(
+            try_decimal_to_chars(17, 37),
+            Err(ConversionError::InvalidBase(37))
+        );
+    }
 }
@@ -2,27 +2,91 @@ use cargo_snippet::snippet;
 
 // 素数判定
 // 2 ~ sqrt(n)の整数で割れるかだけを見ればよい
+// 上限は浮動小数点の sqrt を使わず i <= n / i で整数のまま判定する（i * i のオーバーフローも起きない）
 #[snippet]
 pub fn is_prime(n: usize) -> bool {
     if n <= 1 {
         return false;
     }
-    for i in 2..=((n as f64).sqrt() as usize) {
+    let mut i = 2;
+    while i <= n / i {
         if n % i == 0 {
             return false;
         }
+        i += 1;
     }
     true
 }
 
-// エラトステネスのふるい
-// 1 以上 N 以下の整数が素数かどうかを返す
+// ミラー・ラビン素数判定
+// 64bit の範囲では底を {2,3,5,7,11,13,17,19,23,29,31,37} とすれば決定的に判定できる
+// 乗算はオーバーフローしないように u128 で行う
 #[snippet]
+pub fn is_prime_fast(n: u64) -> bool {
+    fn mul(a: u64, b: u64, m: u64) -> u64 {
+        (a as u128 * b as u128 % m as u128) as u64
+    }
+    fn pow(mut base: u64, mut exp: u64, m: u64) -> u64 {
+        let mut result = 1;
+        base %= m;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = mul(result, base, m);
+            }
+            base = mul(base, base, m);
+            exp >>= 1;
+        }
+        result
+    }
+
+    const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+    for &p in &WITNESSES {
+        if n % p == 0 {
+            return n == p;
+        }
+    }
+
+    // n-1 = d * 2^s
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    'witness: for &a in &WITNESSES {
+        let mut x = pow(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+// エラトステネスのふるい
+// 1 以上 N 以下の素数を列挙する
+#[snippet(include = "eratosthenes_sieve_table")]
 pub fn eratosthenes_sieve(n: usize) -> Vec<usize> {
+    eratosthenes_sieve_table(n).1
+}
+
+// エラトステネスのふるい
+// 0 以上 N 以下の整数が素数かどうかの表と、素数の一覧を返す
+#[snippet]
+pub fn eratosthenes_sieve_table(n: usize) -> (Vec<bool>, Vec<usize>) {
     let mut primes = vec![];
     let mut is_prime = vec![true; n + 1];
     is_prime[0] = false;
-    is_prime[1] = false;
+    if n >= 1 {
+        is_prime[1] = false;
+    }
     for i in 2..=n {
         if is_prime[i] {
             primes.push(i);
@@ -33,7 +97,50 @@ pub fn eratosthenes_sieve(n: usize) -> Vec<usize> {
             }
         }
     }
-    primes
+    (is_prime, primes)
+}
+
+// 区間ふるい
+// [lo, hi] の素数を列挙する。√hi 以下の素数で区間内の倍数を消していく
+// 計算量：O(√hi log log √hi + (hi - lo) log log hi)
+#[snippet(include = "eratosthenes_sieve")]
+pub fn segmented_sieve(lo: u64, hi: u64) -> Vec<u64> {
+    let lo = lo.max(2);
+    if hi < lo {
+        return vec![];
+    }
+
+    // floor(√hi) を浮動小数点の誤差を補正して求める
+    let mut r = (hi as f64).sqrt() as u64;
+    while r as u128 * r as u128 > hi as u128 {
+        r -= 1;
+    }
+    while (r + 1) as u128 * (r + 1) as u128 <= hi as u128 {
+        r += 1;
+    }
+
+    let len = (hi - lo + 1) as usize;
+    let mut is_prime = vec![true; len];
+    for p in eratosthenes_sieve(r as usize) {
+        let p = p as u64;
+        // 区間内に p の倍数が収まらないときは u64 に収まらないこともある
+        let mut j = match lo.div_ceil(p).checked_mul(p) {
+            Some(first) => first.max(p * p),
+            None => continue,
+        };
+        while j <= hi {
+            is_prime[(j - lo) as usize] = false;
+            match j.checked_add(p) {
+                Some(next) => j = next,
+                None => break,
+            }
+        }
+    }
+
+    (0..len)
+        .filter(|&i| is_prime[i])
+        .map(|i| lo + i as u64)
+        .collect()
 }
 
 // 素因数分解
@@ -63,6 +170,83 @@ pub fn prime_factors(mut n: usize) -> Vec<(usize, usize)> {
     factors
 }
 
+// 線形ふるい
+// spf[x] は x を割り切る最小の素数（spf[0] = 0, spf[1] = 1 とする）
+// 各合成数は最小素因数によってちょうど一度だけ消されるので O(N)
+#[snippet]
+pub fn smallest_prime_factor_sieve(n: usize) -> Vec<usize> {
+    let mut spf = vec![0; n + 1];
+    let mut primes = vec![];
+    if n >= 1 {
+        spf[1] = 1;
+    }
+    for i in 2..=n {
+        if spf[i] == 0 {
+            spf[i] = i;
+            primes.push(i);
+        }
+        for &p in &primes {
+            if p > spf[i] || i * p > n {
+                break;
+            }
+            spf[i * p] = p;
+        }
+    }
+    spf
+}
+
+// 最小素因数テーブルを使った素因数分解
+// 計算量：O(log x)
+#[snippet]
+pub fn factorize_with_spf(mut x: usize, spf: &[usize]) -> Vec<(usize, usize)> {
+    let mut factors: Vec<(usize, usize)> = Vec::new();
+
+    while x > 1 {
+        let p = spf[x];
+        let mut count = 0;
+        while x % p == 0 {
+            count += 1;
+            x /= p;
+        }
+        factors.push((p, count));
+    }
+
+    factors
+}
+
+// オイラーのφ関数
+// φ(n) = n * Π(1 - 1/p)   (p は n の素因数)
+#[snippet(include = "prime_factors")]
+pub fn euler_phi(n: usize) -> usize {
+    if n == 0 {
+        return 0;
+    }
+    let mut phi = n;
+    for (p, _) in prime_factors(n) {
+        phi = phi / p * (p - 1);
+    }
+    phi
+}
+
+// 0 以上 N 以下の φ をまとめて求める
+// エラトステネスのふるいと同様に、素数 p の倍数に (1 - 1/p) を掛けていく
+// 計算量：O(N log log N)
+#[snippet]
+pub fn totient_sieve(n: usize) -> Vec<usize> {
+    let mut phi: Vec<usize> = (0..=n).collect();
+    for i in 2..=n {
+        if phi[i] != i {
+            continue;
+        }
+        let mut j = i;
+        while j <= n {
+            phi[j] = phi[j] / i * (i - 1);
+            j += i;
+        }
+    }
+    phi
+}
+
 // 約数の列挙
 #[snippet]
 pub fn divisors(n: usize) -> Vec<usize> {
@@ -81,6 +265,31 @@ pub fn divisors(n: usize) -> Vec<usize> {
     divisors
 }
 
+// 約数の個数
+// n = Π p_i^e_i のとき Π(e_i + 1)
+#[snippet(include = "prime_factors")]
+pub fn num_divisors(n: usize) -> usize {
+    prime_factors(n).iter().map(|&(_, e)| e + 1).product()
+}
+
+// 約数の総和
+// n = Π p_i^e_i のとき Π(1 + p_i + p_i^2 + ... + p_i^e_i) = Π((p_i^(e_i+1) - 1) / (p_i - 1))
+// p^(e+1) を経由しないように等比数列の和を直接足し上げる
+#[snippet(include = "prime_factors")]
+pub fn sum_divisors(n: usize) -> usize {
+    let mut sum = 1;
+    for (p, e) in prime_factors(n) {
+        let mut term = 1;
+        let mut pk = 1;
+        for _ in 0..e {
+            pk *= p;
+            term += pk;
+        }
+        sum *= term;
+    }
+    sum
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -92,6 +301,41 @@ mod tests {
         assert!(!is_prime(1));
     }
 
+    #[test]
+    fn test_is_prime_perfect_square() {
+        assert!(!is_prime(4));
+        assert!(!is_prime(25));
+        assert!(!is_prime(994_009)); // 997^2
+        assert!(!is_prime(998_001)); // 999^2
+        assert!(is_prime(994_013));
+
+        // Large prime square: the loop must reach i == sqrt(n) exactly
+        assert!(!is_prime(9_007_205_210_252_209)); // 94906297^2
+    }
+
+    #[test]
+    fn test_is_prime_fast() {
+        let primes = eratosthenes_sieve(5000);
+        let mut is_p = vec![false; 5001];
+        for &p in &primes {
+            is_p[p] = true;
+        }
+        for (n, &expected) in is_p.iter().enumerate() {
+            assert_eq!(is_prime_fast(n as u64), expected);
+        }
+
+        assert!(is_prime_fast(1_000_000_007));
+        assert!(!is_prime_fast(1_000_000_003));
+        assert!(is_prime_fast(1_000_000_000_000_000_003));
+        assert!(!is_prime_fast(1_000_000_000_000_000_001));
+        assert!(is_prime_fast(18_446_744_073_709_551_557)); // largest prime below 2^64
+        assert!(!is_prime_fast(u64::MAX));
+        // Strong pseudoprime to bases 2, 3, 5, 7, 11
+        assert!(!is_prime_fast(2_152_302_898_747));
+        // 999999937^2
+        assert!(!is_prime_fast(999_999_874_000_003_969));
+    }
+
     #[test]
     fn test_eratosthenes_sieve() {
         let primes_1 = vec![];
@@ -107,6 +351,42 @@ mod tests {
         assert_eq!(eratosthenes_sieve(30), primes_30);
     }
 
+    #[test]
+    fn test_eratosthenes_sieve_table() {
+        let n = 1000;
+        let (table, primes) = eratosthenes_sieve_table(n);
+        assert_eq!(table.len(), n + 1);
+        for (i, &p) in table.iter().enumerate() {
+            assert_eq!(p, is_prime(i));
+        }
+        assert_eq!(primes, eratosthenes_sieve(n));
+
+        assert_eq!(eratosthenes_sieve_table(0), (vec![false], vec![]));
+    }
+
+    #[test]
+    fn test_segmented_sieve() {
+        assert_eq!(
+            segmented_sieve(0, 30),
+            vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]
+        );
+        assert_eq!(segmented_sieve(24, 28), vec![]);
+        assert_eq!(segmented_sieve(30, 10), vec![]);
+        assert_eq!(segmented_sieve(0, 1), vec![]);
+        assert_eq!(segmented_sieve(1, 2), vec![2]);
+
+        let primes: Vec<u64> = eratosthenes_sieve(10_000)
+            .into_iter()
+            .map(|p| p as u64)
+            .collect();
+        assert_eq!(segmented_sieve(0, 10_000), primes);
+
+        let lo = 1_000_000_000_000;
+        let hi = lo + 10_000;
+        let expected: Vec<u64> = (lo..=hi).filter(|&n| is_prime_fast(n)).collect();
+        assert_eq!(segmented_sieve(lo, hi), expected);
+    }
+
     #[test]
     fn test_prime_factors() {
         let n1 = 60;
@@ -122,6 +402,58 @@ mod tests {
         assert_eq!(result3, vec![(101, 1)]);
     }
 
+    #[test]
+    fn test_smallest_prime_factor_sieve() {
+        let spf = smallest_prime_factor_sieve(20);
+        assert_eq!(
+            spf,
+            vec![0, 1, 2, 3, 2, 5, 2, 7, 2, 3, 2, 11, 2, 13, 2, 3, 2, 17, 2, 19, 2]
+        );
+
+        assert_eq!(smallest_prime_factor_sieve(0), vec![0]);
+        assert_eq!(smallest_prime_factor_sieve(1), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_factorize_with_spf() {
+        let n = 1000;
+        let spf = smallest_prime_factor_sieve(n);
+        for x in 1..=n {
+            assert_eq!(factorize_with_spf(x, &spf), prime_factors(x));
+        }
+    }
+
+    #[test]
+    fn test_num_divisors_and_sum_divisors() {
+        for n in 1..=2000 {
+            let d = divisors(n);
+            assert_eq!(num_divisors(n), d.len());
+            assert_eq!(sum_divisors(n), d.iter().sum::<usize>());
+        }
+
+        assert_eq!(num_divisors(735_134_400), 1344);
+        assert_eq!(sum_divisors(1_000_000_007), 1_000_000_008);
+    }
+
+    #[test]
+    fn test_euler_phi() {
+        assert_eq!(euler_phi(1), 1);
+        assert_eq!(euler_phi(10), 4);
+        assert_eq!(euler_phi(36), 12);
+        assert_eq!(euler_phi(1_000_000_007), 1_000_000_006);
+    }
+
+    #[test]
+    fn test_totient_sieve() {
+        let n = 1000;
+        let phi = totient_sieve(n);
+        assert_eq!(phi.len(), n + 1);
+        assert_eq!(phi[0], 0);
+        for (i, &p) in phi.iter().enumerate().skip(1) {
+            assert_eq!(p, euler_phi(i));
+        }
+    }
+
     #[test]
     fn test_divisors() {
         let n1 = 60;
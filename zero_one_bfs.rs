@@ -1,12 +1,25 @@
 use cargo_snippet::snippet;
 
-#[snippet]
+#[snippet(include = "zero_one_bfs_with")]
 // https://drken1215.hatenablog.com/entry/2021/07/30/024800
 pub fn zero_one_bfs(
     field: &[Vec<char>],
     h: usize,
     w: usize,
     start: (usize, usize),
+) -> Vec<Vec<usize>> {
+    zero_one_bfs_with(field, h, w, start, |c| if c == '#' { 1 } else { 0 })
+}
+
+// edge_cost(c) はマス c に入るコストで、0 か 1 を返す必要があります。
+// 2 以上を返すとデバッグビルドでは panic しますが、リリースビルドではコスト 1 として扱われます。
+#[snippet]
+pub fn zero_one_bfs_with<F: Fn(char) -> u8>(
+    field: &[Vec<char>],
+    h: usize,
+    w: usize,
+    start: (usize, usize),
+    edge_cost: F,
 ) -> Vec<Vec<usize>> {
     let mut dist = vec![vec![std::usize::MAX; w]; h];
     let mut deque = std::collections::VecDeque::new();
@@ -29,7 +42,10 @@ pub fn zero_one_bfs(
             let nx = nx as usize;
             let ny = ny as usize;
 
-            if field[nx][ny] != '#' {
+            let cost = edge_cost(field[nx][ny]);
+            debug_assert!(cost <= 1, "edge_cost must return 0 or 1");
+
+            if cost == 0 {
                 if dist[nx][ny] > dist[x][y] {
                     dist[nx][ny] = dist[x][y];
                     deque.push_front((nx, ny));
@@ -46,6 +62,101 @@ pub fn zero_one_bfs(
     dist
 }
 
+// prev[x][y] は (x, y) に最短で入ってくる直前のマス（始点は None）
+// 距離が真に改善したときだけ更新するので、prev をたどると dist と同じコストの経路になる
+#[snippet]
+#[allow(clippy::type_complexity)]
+pub fn zero_one_bfs_with_prev(
+    field: &[Vec<char>],
+    h: usize,
+    w: usize,
+    start: (usize, usize),
+) -> (Vec<Vec<usize>>, Vec<Vec<Option<(usize, usize)>>>) {
+    let mut dist = vec![vec![usize::MAX; w]; h];
+    let mut prev = vec![vec![None; w]; h];
+    let mut deque = std::collections::VecDeque::new();
+
+    let dx = [1, 0, -1, 0];
+    let dy = [0, 1, 0, -1];
+
+    dist[start.0][start.1] = 0;
+    deque.push_front(start);
+
+    while let Some((x, y)) = deque.pop_front() {
+        for dir in 0..4 {
+            let nx = x as i32 + dx[dir];
+            let ny = y as i32 + dy[dir];
+
+            if nx < 0 || h as i32 <= nx || ny < 0 || w as i32 <= ny {
+                continue;
+            }
+
+            let nx = nx as usize;
+            let ny = ny as usize;
+
+            if field[nx][ny] != '#' {
+                if dist[nx][ny] > dist[x][y] {
+                    dist[nx][ny] = dist[x][y];
+                    prev[nx][ny] = Some((x, y));
+                    deque.push_front((nx, ny));
+                }
+            } else if dist[nx][ny] > dist[x][y] + 1 {
+                dist[nx][ny] = dist[x][y] + 1;
+                prev[nx][ny] = Some((x, y));
+                deque.push_back((nx, ny));
+            }
+        }
+    }
+
+    (dist, prev)
+}
+
+// prev をたどって始点から target までのマスの列を返します。
+#[snippet]
+pub fn restore_grid_path(
+    prev: &[Vec<Option<(usize, usize)>>],
+    target: (usize, usize),
+) -> Vec<(usize, usize)> {
+    let mut path = vec![target];
+    let mut cur = target;
+    while let Some(p) = prev[cur.0][cur.1] {
+        path.push(p);
+        cur = p;
+    }
+    path.reverse();
+    path
+}
+
+// 辺の重みが 0 か 1 のグラフ上の 01-BFS
+// 重み 0 の辺は deque の先頭に、重み 1 の辺は末尾に積む
+#[snippet]
+pub fn zero_one_bfs_graph(graph: &Vec<Vec<(usize, u8)>>, start: usize) -> Vec<usize> {
+    let n = graph.len();
+    let mut dist = vec![usize::MAX; n];
+    let mut deque = std::collections::VecDeque::new();
+
+    dist[start] = 0;
+    deque.push_front(start);
+
+    while let Some(u) = deque.pop_front() {
+        for &(v, weight) in &graph[u] {
+            debug_assert!(weight <= 1);
+            let new_dist = dist[u] + weight as usize;
+            if new_dist >= dist[v] {
+                continue;
+            }
+            dist[v] = new_dist;
+            if weight == 0 {
+                deque.push_front(v);
+            } else {
+                deque.push_back(v);
+            }
+        }
+    }
+
+    dist
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,4 +185,94 @@ mod tests {
         ];
         assert_eq!(dist, expected);
     }
+
+    #[test]
+    fn test_zero_one_bfs_with_prev() {
+        let field = vec![
+            vec!['.', '.', '.', '.', '.', '.'],
+            vec!['#', '#', '#', '.', '.', '.'],
+            vec!['#', '#', '#', '.', '.', '.'],
+            vec!['#', '#', '#', '#', '#', '#'],
+            vec!['.', '.', '.', '#', '#', '#'],
+            vec!['.', '.', '#', '#', '#', '#'],
+        ];
+        let h = field.len();
+        let w = field[0].len();
+        let start = (0, 5);
+        let (dist, prev) = zero_one_bfs_with_prev(&field, h, w, start);
+        assert_eq!(dist, zero_one_bfs(&field, h, w, start));
+        assert_eq!(prev[start.0][start.1], None);
+
+        for target in [(5, 0), (5, 2), (3, 0), (0, 0)] {
+            let path = restore_grid_path(&prev, target);
+            assert_eq!(path[0], start);
+            assert_eq!(path[path.len() - 1], target);
+
+            let mut cost = 0;
+            for i in 1..path.len() {
+                let (px, py) = path[i - 1];
+                let (x, y) = path[i];
+                assert_eq!(px.abs_diff(x) + py.abs_diff(y), 1);
+                if field[x][y] == '#' {
+                    cost += 1;
+                }
+            }
+            assert_eq!(cost, dist[target.0][target.1]);
+        }
+
+        assert_eq!(restore_grid_path(&prev, start), vec![start]);
+    }
+
+    #[test]
+    fn test_zero_one_bfs_graph() {
+        let graph: Vec<Vec<(usize, u8)>> = vec![
+            vec![(1, 1), (2, 0)],
+            vec![(3, 0)],
+            vec![(1, 0), (4, 1)],
+            vec![(4, 1), (5, 0)],
+            vec![(5, 1)],
+            vec![],
+            vec![(0, 0)],
+        ];
+        let dist = zero_one_bfs_graph(&graph, 0);
+        assert_eq!(dist, vec![0, 0, 0, 0, 1, 0, usize::MAX]);
+
+        let weighted: Vec<Vec<(usize, usize)>> = graph
+            .iter()
+            .map(|edges| edges.iter().map(|&(v, c)| (v, c as usize)).collect())
+            .collect();
+        for start in 0..graph.len() {
+            assert_eq!(
+                zero_one_bfs_graph(&graph, start),
+                crate::dijkstra::dijkstra(&weighted, start)
+            );
+        }
+    }
+
+    #[test]
+    fn test_zero_one_bfs_with_inverted_cost() {
+        // Entering '.' costs 1 and entering '#' is free
+        let field = vec![
+            vec!['#', '.', '#'],
+            vec!['#', '.', '#'],
+            vec!['#', '#', '#'],
+        ];
+        let h = field.len();
+        let w = field[0].len();
+        let dist = zero_one_bfs_with(&field, h, w, (0, 0), |c| if c == '.' { 1 } else { 0 });
+        let expected = vec![vec![0, 1, 0], vec![0, 1, 0], vec![0, 0, 0]];
+        assert_eq!(dist, expected);
+
+        let dist = zero_one_bfs_with(&field, h, w, (0, 1), |c| if c == '.' { 1 } else { 0 });
+        let expected = vec![vec![0, 0, 0], vec![0, 1, 0], vec![0, 0, 0]];
+        assert_eq!(dist, expected);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_zero_one_bfs_with_invalid_cost() {
+        let field = vec![vec!['.', '.']];
+        zero_one_bfs_with(&field, 1, 2, (0, 0), |_| 2);
+    }
 }